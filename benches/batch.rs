@@ -0,0 +1,105 @@
+use criterion::{Criterion, criterion_group, criterion_main};
+use egglog::EGraph;
+use egglog_macros::egglog_ty;
+use egglog_wrapper::{basic_tx_no_vt, basic_tx_vt, collect_string_type_defs, tx_vt::TxVT};
+use std::{cell::RefCell, hint::black_box, sync::OnceLock};
+
+#[egglog_ty]
+enum Cons {
+    Value { v: i64, con: Box<Cons> },
+    End {},
+}
+
+const CHAIN_LEN: i64 = 200;
+
+/// build a chain of `CHAIN_LEN` nodes and return its head
+fn build_chain<T: TxSgl>() -> Cons<T> {
+    let mut head = Cons::<T>::new_end().as_ref().clone();
+    for i in 0..CHAIN_LEN {
+        head = Cons::new_value(i, &head).as_ref().clone();
+    }
+    head
+}
+
+/// split egglog program into its top level s-expressions
+fn split_commands(program: &str) -> impl Iterator<Item = &str> {
+    let (mut depth, mut start) = (0, 0);
+    program.char_indices().filter_map(move |(idx, c)| match c {
+        '(' => {
+            if depth == 0 {
+                start = idx;
+            }
+            depth += 1;
+            None
+        }
+        ')' => {
+            depth -= 1;
+            (depth == 0).then(|| &program[start..=idx])
+        }
+        _ => None,
+    })
+}
+
+thread_local! {
+    /// egraph of PerNodeTx, EGraph is not Send so it is kept by the benchmarking thread
+    static PER_NODE_EGRAPH: RefCell<EGraph> = RefCell::new({
+        let mut egraph = EGraph::default();
+        egraph.parse_and_run_program(None, &collect_string_type_defs()).unwrap();
+        egraph
+    });
+}
+
+/// TxVT running every node of a commit as a program of its own, as commits were sent before
+/// they were batched
+struct PerNodeTx;
+impl SingletonGetter for PerNodeTx {
+    type RetTy = TxVT;
+    fn sgl() -> &'static TxVT {
+        static INSTANCE: OnceLock<TxVT> = OnceLock::new();
+        INSTANCE.get_or_init(|| {
+            TxVT::new_with_sink(|command| {
+                let TxCommand::StringCommand { string_command } = command else {
+                    unreachable!("commit only sends string commands")
+                };
+                PER_NODE_EGRAPH.with_borrow_mut(|egraph| {
+                    for command in split_commands(&string_command) {
+                        egraph.parse_and_run_program(None, command).unwrap();
+                    }
+                })
+            })
+        })
+    }
+}
+
+/// compare sending one egglog program per node with sending one program per transaction/commit
+fn batch(c: &mut Criterion) {
+    // initialize egraphs first so that parsing type definitions is not measured
+    PlainTx::sgl();
+    BatchTx::sgl();
+    CommitTx::sgl();
+    PerNodeTx::sgl();
+    PER_NODE_EGRAPH.with(|_| ());
+
+    let mut group = c.benchmark_group("build chain");
+    group.sample_size(10);
+    group.bench_function("TxNoVT, one program per node", |b| {
+        b.iter(|| black_box(build_chain::<PlainTx>()))
+    });
+    group.bench_function("TxNoVT, one program per transaction", |b| {
+        b.iter(|| BatchTx::sgl().transaction(|| black_box(build_chain::<BatchTx>())))
+    });
+    group.bench_function("TxVT, one program per node", |b| {
+        b.iter(|| build_chain::<PerNodeTx>().commit())
+    });
+    group.bench_function("TxVT, one program per commit", |b| {
+        b.iter(|| build_chain::<CommitTx>().commit())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, batch);
+criterion_main!(benches);
+
+basic_tx_no_vt!(PlainTx);
+basic_tx_no_vt!(BatchTx);
+basic_tx_vt!(CommitTx);
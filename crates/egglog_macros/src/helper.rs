@@ -1,12 +1,34 @@
 use core::panic;
 
+use darling::FromMeta;
 use proc_macro_crate::{FoundCrate, crate_name};
 use proc_macro2::{Ident, Span, TokenStream};
-use quote::{ToTokens, format_ident, quote};
-use syn::{Fields, GenericArgument, PathArguments, Type, Variant};
+use heck::ToUpperCamelCase;
+use quote::{ToTokens, format_ident, quote, quote_spanned};
+use syn::{
+    Data, DataStruct, DeriveInput, Fields, GenericArgument, PathArguments, Type, Variant,
+};
 
-pub const PANIC_TY_LIST: [&'static str; 4] = ["i32", "u32", "u64", "f32"];
-pub const EGGLOG_BASIC_TY_LIST: [&'static str; 3] = ["String", "i64", "f64"];
+/// rust types stored as egglog literals, they are passed by value rather than by node
+pub const EGGLOG_BASIC_TY_LIST: [&str; 12] = [
+    "String", "i64", "f64", "i8", "i16", "i32", "u8", "u16", "u32", "f32", "bool", "()",
+];
+
+/// integers which don't fit in i64 of egglog, rejected rather than failing at commit time
+pub const EGGLOG_WIDE_INT_LIST: [&str; 2] = ["u64", "usize"];
+
+/// map rust type to the egglog sort storing it
+///
+/// integers are widened to i64, f32 is widened to f64,
+/// `()` is stored by Unit, other types are user defined sorts and keep their names
+pub fn egglog_sort_of(ty: &Type) -> TokenStream {
+    match ty.to_token_stream().to_string().as_str() {
+        "i8" | "i16" | "i32" | "i64" | "u8" | "u16" | "u32" => quote!(i64),
+        "f32" | "f64" => quote!(f64),
+        "()" => quote!(Unit),
+        _ => ty.to_token_stream(),
+    }
+}
 
 pub fn egglog_wrapper_path() -> proc_macro2::TokenStream {
     match (
@@ -94,381 +116,1068 @@ pub fn inventory_wrapper_path() -> proc_macro2::TokenStream {
         _ => quote!(::inventory),
     }
 }
-pub fn is_vec_type(ty: &Type) -> bool {
-    if let Type::Path(type_path) = ty {
-        if let Some(segment) = type_path.path.segments.last() {
-            if segment.ident == "Vec" {
-                return true;
-            }
-        }
+pub fn is_box_type(ty: &Type) -> bool {
+    if let Type::Path(type_path) = ty
+        && let Some(segment) = type_path.path.segments.last()
+    {
+        return segment.ident == "Box";
     }
     false
 }
-pub fn is_box_type(ty: &Type) -> bool {
-    if let Type::Path(type_path) = ty {
-        if let Some(segment) = type_path.path.segments.last() {
-            if segment.ident == "Box" {
-                return true;
-            }
-        }
+pub fn is_option_type(ty: &Type) -> bool {
+    if let Type::Path(type_path) = ty
+        && let Some(segment) = type_path.path.segments.last()
+    {
+        return segment.ident == "Option";
     }
     false
 }
-pub fn get_first_generic(ty: &Type) -> &Type {
-    if let Type::Path(type_path) = ty {
-        if let Some(segment) = type_path.path.segments.last() {
-            if let PathArguments::AngleBracketed(args) = &segment.arguments {
-                let arg = args
-                    .args
-                    .iter()
-                    .nth(0)
-                    .expect("type should at least have one generic");
-                if let GenericArgument::Type(inner_ty) = arg {
-                    // inner_ty 就是 Vec<T> 中的 T 类型
-                    return inner_ty;
+/// name of type used by error messages, `Box<T>` is named `Box`
+pub fn ty_name(ty: &Type) -> String {
+    match ty {
+        Type::Path(type_path) => match type_path.path.segments.last() {
+            Some(segment) => segment.ident.to_string(),
+            None => ty.to_token_stream().to_string(),
+        },
+        _ => ty.to_token_stream().to_string(),
+    }
+}
+
+/// first generic type arg, `Box<T>` returns `T`
+pub fn try_first_generic(ty: &Type) -> syn::Result<&Type> {
+    get_generics(ty).into_iter().next().ok_or_else(|| {
+        syn::Error::new_spanned(ty, format!("`{}` needs a type argument", ty_name(ty)))
+    })
+}
+
+/// node type referenced by field
+pub enum NodeTy {
+    /// type param of generic `#[egglog_ty]`, such as `E` of `List<E>`
+    Param(Ident),
+    /// `#[egglog_ty]` type, generic ones carry their type args such as `List<Ctl>`
+    Ty(Ident, Vec<NodeTy>),
+}
+
+impl NodeTy {
+    pub fn ident(&self) -> &Ident {
+        match self {
+            NodeTy::Param(ident) | NodeTy::Ty(ident, _) => ident,
+        }
+    }
+    /// whether type params of the generic type being defined occur in it
+    pub fn has_param(&self) -> bool {
+        match self {
+            NodeTy::Param(_) => true,
+            NodeTy::Ty(_, args) => args.iter().any(NodeTy::has_param),
+        }
+    }
+    /// whether it's an instance of generic type, such as `List<Ctl>`
+    pub fn is_instance(&self) -> bool {
+        matches!(self, NodeTy::Ty(_, args) if !args.is_empty())
+    }
+    /// EgglogTy impl, `CtlTy` for `Ctl` and `ListTy<CtlTy>` for `List<Ctl>`
+    pub fn egglogty(&self) -> TokenStream {
+        match self {
+            NodeTy::Param(ident) => ident.to_token_stream(),
+            NodeTy::Ty(ident, args) => {
+                let name_egglogty = format_ident!("{}Ty", ident, span = ident.span());
+                if args.is_empty() {
+                    name_egglogty.to_token_stream()
+                } else {
+                    let args = args.iter().map(NodeTy::egglogty);
+                    quote_spanned! {ident.span()=> #name_egglogty<#(#args),*>}
                 }
             }
         }
     }
-    panic!("first generic generic can only be Type")
+    /// node type bound to Tx `T` with variant `v`, such as `Ctl<T, V>` or `List<T, CtlTy, V>`
+    ///
+    /// node of type param is known only by `EgglogTyNode`, its variant is erased
+    pub fn node(&self, v: TokenStream) -> TokenStream {
+        match self {
+            NodeTy::Param(ident) => quote!(<#ident as EgglogTyNode<T>>::Node),
+            NodeTy::Ty(ident, args) => {
+                let args = args.iter().map(NodeTy::egglogty);
+                quote!(#ident<T, #(#args,)* #v>)
+            }
+        }
+    }
+    /// egglog sort known by macro, type args are mangled into the name so `List<Ctl>` is `ListCtl`
+    pub fn static_sort(&self) -> Ident {
+        match self {
+            NodeTy::Param(_) => unreachable!("sort of type param is known at runtime"),
+            NodeTy::Ty(ident, args) => args.iter().fold(ident.clone(), |sort, arg| {
+                format_ident!("{}{}", sort, arg.static_sort())
+            }),
+        }
+    }
+    /// expression of egglog sort evaluated at runtime, type params are resolved by `EgglogTy::sort_name`
+    pub fn sort_expr(&self) -> TokenStream {
+        let egglogty = self.egglogty();
+        quote!(<#egglogty as EgglogTy>::sort_name())
+    }
 }
 
-/// given variant a{ x:X, y:Y}
-/// return vec![ x:XSym, y:YSym ]
-pub fn variants_to_sym_typed_ident_list(variant: &Variant) -> Vec<proc_macro2::TokenStream> {
-    let types_and_idents = match &variant.fields {
-        Fields::Named(fields_named) => fields_named.named.iter(),
-        Fields::Unit => {
-            panic!("add `{{}}` to the unit variant")
-        }
-        _ => panic!("only support named fields"),
-    }
-    .map(|f| {
-        let f_ident = f
-            .ident
-            .as_ref()
-            .expect("don't support unnamed field")
-            .clone();
-        // if it's a box type we should read the first generic
-        if is_box_type(&f.ty) {
-            (get_first_generic(&f.ty).clone(), f_ident)
+/// parse node type, type args of generic `#[egglog_ty]` should be `#[egglog_ty]` types
+fn try_node_ty(ty: &Type, params: &[Ident]) -> syn::Result<NodeTy> {
+    let not_node = || {
+        syn::Error::new_spanned(
+            ty,
+            "type argument of generic `#[egglog_ty]` should be an `#[egglog_ty]` type",
+        )
+    };
+    let Type::Path(type_path) = ty else {
+        return Err(not_node());
+    };
+    let Some(segment) = type_path.path.segments.last() else {
+        return Err(not_node());
+    };
+    if type_path.qself.is_some()
+        || EGGLOG_BASIC_TY_LIST.contains(&ty.to_token_stream().to_string().as_str())
+        || is_box_type(ty)
+        || is_option_type(ty)
+        || is_container_type(ty)
+    {
+        return Err(not_node());
+    }
+    if type_path.path.segments.len() == 1 && params.contains(&segment.ident) {
+        return if segment.arguments.is_none() {
+            Ok(NodeTy::Param(segment.ident.clone()))
         } else {
-            (f.ty.clone(), f_ident)
-        }
-    })
-    .map(|(f1, f2)| {
-        let sym_ty = match f1.to_token_stream().to_string().as_str() {
-            x if PANIC_TY_LIST.contains(&x) => {
-                panic!("{} not supported", x)
-            }
-            x if EGGLOG_BASIC_TY_LIST.contains(&x) => f1.to_token_stream(),
-            _ => {
-                let f1_ident = match &f1 {
-                    Type::Path(type_path) => {
-                        type_path
-                            .path
-                            .segments
-                            .last()
-                            .expect("impossible")
-                            .clone()
-                            .ident
-                    }
-                    _ => panic!(),
-                };
-                let name_egglogty = format_ident!("{}Ty", f1_ident);
-                quote!( Sym<#name_egglogty>)
+            Err(syn::Error::new_spanned(ty, "type param could not take type arguments"))
+        };
+    }
+    let args = match &segment.arguments {
+        PathArguments::None => vec![],
+        PathArguments::AngleBracketed(args) => args
+            .args
+            .iter()
+            .map(|arg| match arg {
+                GenericArgument::Type(arg) => try_node_ty(arg, params),
+                arg => Err(syn::Error::new_spanned(
+                    arg,
+                    "only type arguments are supported by generic `#[egglog_ty]`",
+                )),
+            })
+            .collect::<syn::Result<Vec<_>>>()?,
+        PathArguments::Parenthesized(_) => return Err(not_node()),
+    };
+    Ok(NodeTy::Ty(segment.ident.clone(), args))
+}
+
+/// how a field of variant is stored in node
+#[allow(clippy::large_enum_variant)]
+pub enum FieldKind {
+    /// egglog literal, stored by value
+    Basic(Type),
+    /// another node, stored by sym. `Box<T>` is unwrapped
+    Node(NodeTy),
+    /// optional egglog literal, stored by value
+    OptionBasic(Type),
+    /// optional node, stored by optional sym
+    OptionNode(Ident),
+}
+
+impl FieldKind {
+    pub fn is_basic(&self) -> bool {
+        matches!(self, FieldKind::Basic(_) | FieldKind::OptionBasic(_))
+    }
+}
+
+/// literal types, `#[egglog_ty]` types, `Box<T>` and `Option<T>` could be fields
+///
+/// `params` are type params of the generic type being defined
+pub fn try_field_kind(ty: &Type, params: &[Ident]) -> syn::Result<FieldKind> {
+    // if it's a box type we should read the first generic
+    let ty = if is_box_type(ty) {
+        try_first_generic(ty)?
+    } else {
+        ty
+    };
+    if is_option_type(ty) {
+        return match try_field_kind(try_first_generic(ty)?, params)? {
+            FieldKind::Basic(ty) => Ok(FieldKind::OptionBasic(ty)),
+            FieldKind::Node(NodeTy::Ty(ident, args)) if args.is_empty() => {
+                Ok(FieldKind::OptionNode(ident))
             }
+            FieldKind::Node(_) => Err(syn::Error::new_spanned(
+                ty,
+                "Option of type param or generic `#[egglog_ty]` is not supported",
+            )),
+            _ => Err(syn::Error::new_spanned(ty, "nested Option is not supported")),
         };
-        let ident = f2;
-        quote! { #ident :#sym_ty}
-    })
-    .collect::<Vec<_>>();
-    types_and_idents
-}
-pub fn variants_to_sym_type_list(variant: &Variant) -> Vec<proc_macro2::TokenStream> {
-    let types = match &variant.fields {
-        Fields::Named(fields_named) => fields_named.named.iter(),
-        Fields::Unit => {
-            panic!("add `{{}}` to the unit variant")
-        }
-        _ => panic!("only support named fields"),
-    }
-    .map(|f| {
-        let f_ident = f
-            .ident
-            .as_ref()
-            .expect("don't support unnamed field")
-            .clone();
-        // if it's a box type we should read the first generic
-        if is_box_type(&f.ty) {
-            (get_first_generic(&f.ty).clone(), f_ident)
-        } else {
-            (f.ty.clone(), f_ident)
-        }
-    })
-    .map(|(f1, _)| {
-        let sym_ty = match f1.to_token_stream().to_string().as_str() {
-            x if PANIC_TY_LIST.contains(&x) => {
-                panic!("{} not supported", x)
+    }
+    if EGGLOG_BASIC_TY_LIST.contains(&ty.to_token_stream().to_string().as_str()) {
+        return Ok(FieldKind::Basic(ty.clone()));
+    }
+    if EGGLOG_WIDE_INT_LIST.contains(&ty.to_token_stream().to_string().as_str()) {
+        return Err(syn::Error::new_spanned(
+            ty,
+            format!(
+                "`{}` could exceed i64 of egglog, use i64 or a narrower integer",
+                ty.to_token_stream()
+            ),
+        ));
+    }
+    match ty {
+        Type::Path(type_path) if type_path.qself.is_none() => {
+            let segment = type_path.path.segments.last().expect("impossible");
+            if is_container_type(ty) {
+                Err(syn::Error::new_spanned(
+                    ty,
+                    format!(
+                        "`{}` could only be the single field of a struct, such as `struct Points {{ v: Vec<Point> }}`",
+                        segment.ident
+                    ),
+                ))
+            } else if matches!(segment.arguments, PathArguments::Parenthesized(_)) {
+                Err(syn::Error::new_spanned(
+                    ty,
+                    "unsupported field type, expect a literal type, an `#[egglog_ty]` type, `Box<T>` or `Option<T>`",
+                ))
+            } else {
+                // type args are only taken by generic `#[egglog_ty]`, such as `List<Ctl>`
+                Ok(FieldKind::Node(try_node_ty(ty, params)?))
             }
-            x if EGGLOG_BASIC_TY_LIST.contains(&x) => f1.to_token_stream(),
-            _ => {
-                let f1_ident = match &f1 {
-                    Type::Path(type_path) => {
-                        type_path
-                            .path
-                            .segments
-                            .last()
-                            .expect("impossible")
-                            .clone()
-                            .ident
-                    }
-                    _ => panic!(),
-                };
-                let name_egglogty = format_ident!("{}Ty", f1_ident);
-                quote!( Sym<#name_egglogty>)
+        }
+        _ => Err(syn::Error::new_spanned(
+            ty,
+            "unsupported field type, expect a literal type, an `#[egglog_ty]` type, `Box<T>` or `Option<T>`",
+        )),
+    }
+}
+pub fn field_kind(ty: &Type, params: &[Ident]) -> FieldKind {
+    try_field_kind(ty, params).expect("field type is checked by check_egglog_ty")
+}
+
+/// name of generated option datatype and names of its `Some`, `None` constructors
+///
+/// `Option<HitBox>` is stored by `(OptionHitBox (SomeHitBox HitBox) (NoneHitBox))`,
+/// literals are named after their egglog sort so `Option<i32>` and `Option<i64>` share `OptionI64`
+pub fn option_sort_of(kind: &FieldKind) -> (Ident, Ident, Ident) {
+    let suffix = match kind {
+        FieldKind::OptionNode(ident) => ident.to_string(),
+        FieldKind::OptionBasic(ty) => egglog_sort_of(ty).to_string().to_upper_camel_case(),
+        _ => panic!("only Option has option sort"),
+    };
+    (
+        format_ident!("Option{}", suffix),
+        format_ident!("Some{}", suffix),
+        format_ident!("None{}", suffix),
+    )
+}
+
+/// collect def of option datatype at runtime, such as `(OptionHitBox (SomeHitBox HitBox) (NoneHitBox))`
+///
+/// inner sort is resolved at runtime as the inner type may be renamed by `#[egglog_ty(name = ..)]`
+pub fn option_sort_collect(kind: &FieldKind) -> TokenStream {
+    let (option, some, none) = option_sort_of(kind);
+    let (option, some, none) = (option.to_string(), some.to_string(), none.to_string());
+    let inner = match kind {
+        FieldKind::OptionNode(ident) => {
+            let name_egglogty = format_ident!("{}Ty", ident);
+            quote!(<#name_egglogty as EgglogTy>::sort_name())
+        }
+        FieldKind::OptionBasic(ty) => {
+            let sort = egglog_sort_of(ty).to_string();
+            quote!(#sort.to_owned())
+        }
+        _ => panic!("only Option has option sort"),
+    };
+    quote! {defs.insert_option(#option, #some, #none, #inner);}
+}
+
+/// egglog sort of field known by macro, such as `i64`, `ListCtl` or `OptionHitBox`
+pub fn kind_sort(kind: &FieldKind) -> TokenStream {
+    match kind {
+        FieldKind::Basic(ty) => egglog_sort_of(ty),
+        FieldKind::Node(node_ty) => node_ty.static_sort().to_token_stream(),
+        FieldKind::OptionBasic(_) | FieldKind::OptionNode(_) => {
+            option_sort_of(kind).0.to_token_stream()
+        }
+    }
+}
+
+/// egglog sort of field as declared by generic type, such as `E` or `(List E)`
+pub fn kind_sort_template(kind: &FieldKind) -> TokenStream {
+    fn template(node_ty: &NodeTy) -> TokenStream {
+        match node_ty {
+            NodeTy::Ty(ident, args) if !args.is_empty() => {
+                let args = args.iter().map(template);
+                quote!((#ident #(#args)*))
             }
-        };
-        quote! { #sym_ty}
+            _ => node_ty.ident().to_token_stream(),
+        }
+    }
+    match kind {
+        FieldKind::Node(node_ty) => template(node_ty),
+        _ => kind_sort(kind),
+    }
+}
+
+/// expression of egglog sort of field evaluated at runtime, sorts of types may be renamed or mangled
+pub fn kind_sort_expr(kind: &FieldKind) -> TokenStream {
+    match kind {
+        FieldKind::Node(node_ty) => node_ty.sort_expr(),
+        _ => {
+            let sort = kind_sort(kind).to_string();
+            quote!(#sort.to_owned())
+        }
+    }
+}
+
+/// name mangled with egglog sorts of type params at runtime, `Cons` of `List<Ctl>` is `ConsCtl`
+pub fn mangled_name(base: &str, params: &[Ident]) -> TokenStream {
+    if params.is_empty() {
+        quote!(#base)
+    } else {
+        quote!([#base, #(<#params as EgglogTy>::sort_name().as_str()),*].concat())
+    }
+}
+
+/// arguments of `#[egglog(name = "...", cost = 10)]` on variant
+#[derive(Debug, Default, FromMeta)]
+#[darling(default)]
+pub struct VariantMeta {
+    /// constructor name replacing the variant name, prefix of type is still prepended
+    pub name: Option<String>,
+    /// cost of constructor used by extraction, egglog defaults it to 1
+    pub cost: Option<u64>,
+}
+
+/// parse `#[egglog(..)]` attributes of variant, other attributes are left to rust
+pub fn variant_meta(variant: &Variant) -> syn::Result<VariantMeta> {
+    let mut meta = VariantMeta::default();
+    for attr in variant.attrs.iter().filter(|attr| attr.path().is_ident("egglog")) {
+        let parsed = VariantMeta::from_meta(&attr.meta)?;
+        if let Some(name) = parsed.name {
+            check_egglog_name(&name, attr)?;
+            meta.name = Some(name);
+        }
+        meta.cost = parsed.cost.or(meta.cost);
+    }
+    Ok(meta)
+}
+
+/// `:cost` option of constructor or function, empty if it's not annotated
+pub fn cost_option(cost: Option<u64>) -> String {
+    cost.map(|cost| format!(" :cost {}", cost)).unwrap_or_default()
+}
+
+/// custom names are spliced into egglog program, so they should be plain symbols
+pub fn check_egglog_name(name: &str, spanned: impl ToTokens) -> syn::Result<()> {
+    if name.is_empty() || name.contains(|c: char| c.is_whitespace() || "()\";".contains(c)) {
+        return Err(syn::Error::new_spanned(
+            spanned,
+            format!("`{}` is not a valid egglog name", name),
+        ));
+    }
+    Ok(())
+}
+
+/// egglog constructor of variant, such as `geo_Cons` of `#[egglog_ty(prefix = "geo_")]`, mangled for generic types
+pub fn ctor_name(variant: &Variant, prefix: &str, params: &[Ident]) -> TokenStream {
+    let base = variant_meta(variant)
+        .expect("checked by check_egglog_ty")
+        .name
+        .unwrap_or_else(|| variant.ident.to_string());
+    mangled_name(&format!("{}{}", prefix, base), params)
+}
+
+/// container field of struct form `#[egglog_ty]`
+#[allow(clippy::large_enum_variant)]
+pub enum ContainerKind {
+    /// `Vec<T>`, stored by egglog Vec sort
+    Vec(FieldKind),
+    /// `HashSet<T>` or `BTreeSet<T>`, stored by egglog Set sort
+    Set(FieldKind),
+    /// `BTreeMap<K, V>`, stored by egglog Map sort
+    Map(FieldKind, FieldKind),
+}
+
+/// all generic type args, `BTreeMap<K, V>` returns `[K, V]`
+pub fn get_generics(ty: &Type) -> Vec<&Type> {
+    if let Type::Path(type_path) = ty
+        && let Some(segment) = type_path.path.segments.last()
+        && let PathArguments::AngleBracketed(args) = &segment.arguments
+    {
+        return args
+            .args
+            .iter()
+            .filter_map(|arg| match arg {
+                GenericArgument::Type(inner_ty) => Some(inner_ty),
+                _ => None,
+            })
+            .collect();
+    }
+    vec![]
+}
+
+fn try_element_kind(ty: &Type, params: &[Ident]) -> syn::Result<FieldKind> {
+    match try_field_kind(ty, params)? {
+        FieldKind::OptionBasic(_) | FieldKind::OptionNode(_) => Err(syn::Error::new_spanned(
+            ty,
+            "Option is not supported as element of container",
+        )),
+        kind => Ok(kind),
+    }
+}
+
+/// whether `ty` is `Vec`, `HashSet`, `BTreeSet` or `BTreeMap`
+pub fn is_container_type(ty: &Type) -> bool {
+    let Type::Path(type_path) = ty else {
+        return false;
+    };
+    type_path.path.segments.last().is_some_and(|segment| {
+        matches!(
+            segment.ident.to_string().as_str(),
+            "Vec" | "HashSet" | "BTreeSet" | "BTreeMap"
+        )
     })
-    .collect::<Vec<_>>();
-    types
-}
-pub fn variant_to_ref_node_list(variant: &Variant, _: &Ident) -> Vec<proc_macro2::TokenStream> {
-    let types_and_idents = match &variant.fields {
-        Fields::Named(fields_named) => fields_named.named.iter(),
-        Fields::Unit => {
-            panic!("add `{{}}` to the unit variant")
-        }
-        _ => panic!("only support named fields"),
-    }
-    .map(|f| {
-        let f_ident = f
-            .ident
-            .as_ref()
-            .expect("don't support unnamed field")
-            .clone();
-        // if it's a box type we should read the first generic
-        if is_box_type(&f.ty) {
-            (get_first_generic(&f.ty).clone(), f_ident)
+}
+
+pub fn try_container_kind(ty: &Type, params: &[Ident]) -> syn::Result<Option<ContainerKind>> {
+    let Type::Path(type_path) = ty else {
+        return Ok(None);
+    };
+    let Some(segment) = type_path.path.segments.last() else {
+        return Ok(None);
+    };
+    let generics = get_generics(ty);
+    let expect_generics = |count: usize, what: &str| {
+        if generics.len() == count {
+            Ok(())
         } else {
-            (f.ty.clone(), f_ident)
+            Err(syn::Error::new_spanned(
+                ty,
+                format!("`{}` needs {}", segment.ident, what),
+            ))
         }
-    })
-    .map(|(f1, f2)| {
-        let node_ty = match f1.to_token_stream().to_string().as_str() {
-            x if PANIC_TY_LIST.contains(&x) => {
-                panic!("{} not supported", x)
+    };
+    match segment.ident.to_string().as_str() {
+        "Vec" => {
+            expect_generics(1, "an element type argument")?;
+            Ok(Some(ContainerKind::Vec(try_element_kind(generics[0], params)?)))
+        }
+        "HashSet" | "BTreeSet" => {
+            expect_generics(1, "an element type argument")?;
+            Ok(Some(ContainerKind::Set(try_element_kind(generics[0], params)?)))
+        }
+        "BTreeMap" => {
+            expect_generics(2, "key and value type arguments")?;
+            Ok(Some(ContainerKind::Map(
+                try_element_kind(generics[0], params)?,
+                try_element_kind(generics[1], params)?,
+            )))
+        }
+        _ => Ok(None),
+    }
+}
+pub fn container_kind(ty: &Type, params: &[Ident]) -> Option<ContainerKind> {
+    try_container_kind(ty, params).expect("container is checked by check_egglog_ty")
+}
+
+impl ContainerKind {
+    /// kinds of elements, `BTreeMap<K, V>` has two
+    pub fn elems(&self) -> Vec<&FieldKind> {
+        match self {
+            ContainerKind::Vec(elem) | ContainerKind::Set(elem) => vec![elem],
+            ContainerKind::Map(key, value) => vec![key, value],
+        }
+    }
+    /// name of egglog container sort
+    fn sort_name(&self) -> &'static str {
+        match self {
+            ContainerKind::Vec(_) => "Vec",
+            ContainerKind::Set(_) => "Set",
+            ContainerKind::Map(..) => "Map",
+        }
+    }
+    /// egglog sort of container evaluated at runtime, used by generic types
+    pub fn sort_expr(&self) -> TokenStream {
+        let container = self.sort_name();
+        let elems = self.elems().into_iter().map(kind_sort_expr);
+        quote!(format!("({} {})", #container, [#(#elems),*].join(" ")))
+    }
+    /// egglog sort of container such as `(Vec Cons)` or `(Map i64 Cons)`, elements are rendered by `elem_sort`
+    pub fn sort(&self, elem_sort: impl Fn(&FieldKind) -> TokenStream) -> TokenStream {
+        let container = format_ident!("{}", self.sort_name());
+        let elems = self.elems().into_iter().map(elem_sort);
+        quote!((#container #(#elems)*))
+    }
+}
+
+/// code generation of container elements, only `Basic` and `Node` could be elements
+impl FieldKind {
+    /// type storing element in node
+    pub fn elem_store_ty(&self) -> TokenStream {
+        match self {
+            FieldKind::Basic(ty) => ty.to_token_stream(),
+            FieldKind::Node(node_ty) => {
+                let name_egglogty = node_ty.egglogty();
+                quote!(Sym<#name_egglogty>)
             }
-            x if EGGLOG_BASIC_TY_LIST.contains(&x) => f1.to_token_stream(),
-            _ => {
-                let f1_ident = match &f1 {
-                    Type::Path(type_path) => {
-                        type_path
-                            .path
-                            .segments
-                            .last()
-                            .expect("impossible")
-                            .clone()
-                            .ident
-                    }
-                    _ => panic!(),
-                };
-                let name_node = format_ident!("{}", f1_ident);
-                quote! { &#name_node<T, impl EgglogEnumVariantTy> }
+            _ => unreachable!(),
+        }
+    }
+    /// type of element taken by new fn
+    pub fn elem_param_ty(&self) -> TokenStream {
+        match self {
+            FieldKind::Basic(ty) => ty.to_token_stream(),
+            FieldKind::Node(node_ty) => {
+                let node = node_ty.node(quote!(()));
+                quote!(&dyn AsRef<#node>)
             }
-        };
-        let ident = f2;
-        quote! { #ident : #node_ty}
-    })
-    .collect::<Vec<_>>();
-    types_and_idents
+            _ => unreachable!(),
+        }
+    }
+    /// owned type built from egglog term
+    pub fn elem_value_ty(&self) -> TokenStream {
+        match self {
+            FieldKind::Basic(ty) => ty.to_token_stream(),
+            FieldKind::Node(node_ty) => node_ty.node(quote!(())),
+            _ => unreachable!(),
+        }
+    }
+    /// convert param `e` to stored element
+    pub fn elem_store_expr(&self, e: &TokenStream) -> TokenStream {
+        match self {
+            FieldKind::Basic(_) => e.clone(),
+            FieldKind::Node(node_ty) => node_sym_expr(node_ty, &quote!(#e.as_ref())),
+            _ => unreachable!(),
+        }
+    }
+    /// convert reference `e` to an owned value to param
+    pub fn elem_value_to_param(&self, e: &TokenStream) -> TokenStream {
+        match self {
+            FieldKind::Basic(_) => quote!(#e.clone()),
+            FieldKind::Node(node_ty) => {
+                let node = node_ty.node(quote!(()));
+                quote!(#e as &dyn AsRef<#node>)
+            }
+            _ => unreachable!(),
+        }
+    }
+    /// render reference `e` to stored element as egglog
+    pub fn elem_render(&self, e: &TokenStream) -> TokenStream {
+        match self {
+            FieldKind::Basic(_) => quote!(#e.to_egglog_lit()),
+            FieldKind::Node(_) => quote!(#e.to_string()),
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// fields of variant with their idents, fields of tuple variant are named `_0`, `_1` ..
+fn variant_fields(variant: &Variant) -> Vec<(Ident, &syn::Field)> {
+    match &variant.fields {
+        Fields::Named(fields_named) => fields_named
+            .named
+            .iter()
+            .map(|f| (f.ident.clone().expect("impossible"), f))
+            .collect(),
+        Fields::Unnamed(fields_unnamed) => fields_unnamed
+            .unnamed
+            .iter()
+            .enumerate()
+            .map(|(idx, f)| (format_ident!("_{}", idx), f))
+            .collect(),
+        Fields::Unit => vec![],
+    }
+}
+
+/// name of the marker type of variant, prefixed by enum name so that variant named as
+/// other types (such as `Scene::Scene`) doesn't shadow them in the generated code
+pub fn variant_marker(name: &Ident, variant: &Variant) -> Ident {
+    format_ident!("{}{}", name, variant.ident)
+}
+
+/// name of set fn of field, `set_x` for field `x` and `set_0` for the first field of tuple variant
+pub fn set_fn_name(field_ident: &Ident) -> Ident {
+    let field_name = field_ident.to_string();
+    match field_name.strip_prefix('_') {
+        Some(idx) if idx.parse::<usize>().is_ok() => format_ident!("set_{}", idx),
+        _ => format_ident!("set_{}", field_ident),
+    }
+}
+
+/// given variant a{ x:X, y:Option<Y>}
+/// return vec![ (x, Node(X)), (y, OptionNode(Y)) ]
+pub fn variant_to_field_kinds(variant: &Variant, params: &[Ident]) -> Vec<(Ident, FieldKind)> {
+    variant_fields(variant)
+        .into_iter()
+        .map(|(f_ident, f)| (f_ident, field_kind(&f.ty, params)))
+        .collect()
+}
+
+/// sym of `node`, node of type param is only known as `EgglogNode` so its sym is typed again
+fn node_sym_expr(node_ty: &NodeTy, node: &TokenStream) -> TokenStream {
+    match node_ty {
+        NodeTy::Param(_) => quote!(Sym::new(#node.cur_sym().inner)),
+        NodeTy::Ty(..) => quote!(#node.sym),
+    }
 }
+
+/// type storing the field in node
+fn field_sym_ty(kind: &FieldKind) -> TokenStream {
+    match kind {
+        FieldKind::Basic(ty) => ty.to_token_stream(),
+        FieldKind::Node(node_ty) => {
+            let name_egglogty = node_ty.egglogty();
+            quote!( Sym<#name_egglogty>)
+        }
+        FieldKind::OptionBasic(ty) => quote!(Option<#ty>),
+        FieldKind::OptionNode(ident) => {
+            let name_egglogty = format_ident!("{}Ty", ident);
+            quote!( Option<Sym<#name_egglogty>>)
+        }
+    }
+}
+
+/// given variant a{ x:X, y:Y}
+/// return vec![ x:XSym, y:YSym ]
+pub fn variants_to_sym_typed_ident_list(
+    variant: &Variant,
+    params: &[Ident],
+) -> Vec<proc_macro2::TokenStream> {
+    variant_to_field_kinds(variant, params)
+        .iter()
+        .map(|(ident, kind)| {
+            let sym_ty = field_sym_ty(kind);
+            quote! { #ident :#sym_ty}
+        })
+        .collect::<Vec<_>>()
+}
+pub fn variants_to_sym_type_list(variant: &Variant, params: &[Ident]) -> Vec<proc_macro2::TokenStream> {
+    variant_to_field_kinds(variant, params)
+        .iter()
+        .map(|(_, kind)| field_sym_ty(kind))
+        .collect::<Vec<_>>()
+}
+pub fn variant_to_ref_node_list(variant: &Variant, params: &[Ident]) -> Vec<proc_macro2::TokenStream> {
+    variant_to_field_kinds(variant, params)
+        .iter()
+        .map(|(ident, kind)| {
+            let node_ty = match kind {
+                FieldKind::Basic(ty) => ty.to_token_stream(),
+                FieldKind::Node(node_ty @ NodeTy::Param(_)) => {
+                    let node = node_ty.node(quote!(()));
+                    quote! { &dyn AsRef<#node> }
+                }
+                FieldKind::Node(node_ty) => {
+                    let node = node_ty.node(quote!(impl EgglogEnumVariantTy));
+                    quote! { &#node }
+                }
+                FieldKind::OptionBasic(ty) => quote! { Option<#ty> },
+                FieldKind::OptionNode(name_node) => quote! { Option<&#name_node<T, ()>> },
+            };
+            quote! { #ident : #node_ty}
+        })
+        .collect::<Vec<_>>()
+}
+/// map every field ident, optional nodes are mapped inside `if let Some(x) = x {..}`
 pub fn variant_to_mapped_ident_list(
     variant: &Variant,
+    params: &[Ident],
     map_basic_ty: impl Fn(&Ident) -> TokenStream,
     map_complex_ty: impl Fn(&Ident) -> TokenStream,
 ) -> Vec<proc_macro2::TokenStream> {
-    let types_and_idents = match &variant.fields {
-        Fields::Named(fields_named) => fields_named.named.iter(),
-        Fields::Unit => {
-            panic!("add `{{}}` to the unit variant")
-        }
-        _ => panic!("only support named fields"),
-    }
-    .map(|f| {
-        let f_ident = f
-            .ident
-            .as_ref()
-            .expect("don't support unnamed field")
-            .clone();
-        // if it's a box type we should read the first generic
-        if is_box_type(&f.ty) {
-            (get_first_generic(&f.ty).clone(), f_ident)
-        } else {
-            (f.ty.clone(), f_ident)
-        }
-    })
-    .map(|(f1, f2)| {
-        let mapped_ident = match f1.to_token_stream().to_string().as_str() {
-            x if PANIC_TY_LIST.contains(&x) => {
-                panic!("{} not supported", x)
-            }
-            x if EGGLOG_BASIC_TY_LIST.contains(&x) => map_basic_ty(&f2),
-            _ => map_complex_ty(&f2),
-        };
-        quote! {  #mapped_ident}
-    })
-    .collect::<Vec<_>>();
-    types_and_idents
-}
-pub fn variants_to_assign_node_field_list(variant: &Variant) -> Vec<proc_macro2::TokenStream> {
-    let types_and_idents = match &variant.fields {
-        Fields::Named(fields_named) => fields_named.named.iter(),
-        Fields::Unit => {
-            panic!("add `{{}}` to the unit variant")
-        }
-        _ => panic!("only support named fields"),
-    }
-    .map(|f| {
-        let f_ident = f
-            .ident
-            .as_ref()
-            .expect("don't support unnamed field")
-            .clone();
-        // if it's a box type we should read the first generic
-        if is_box_type(&f.ty) {
-            (get_first_generic(&f.ty).clone(), f_ident)
-        } else {
-            (f.ty.clone(), f_ident)
-        }
-    })
-    .zip(variant_to_field_ident(variant))
-    .map(|((f1, f2), ident)| {
-        let node_ty = match f1.to_token_stream().to_string().as_str() {
-            x if PANIC_TY_LIST.contains(&x) => {
-                panic!("{} not supported", x)
-            }
-            x if EGGLOG_BASIC_TY_LIST.contains(&x) => ident.to_token_stream(),
-            _ => {
-                quote!(#ident.sym)
-            }
-        };
-        let ident = f2;
-        quote! { #ident : #node_ty}
-    })
-    .collect::<Vec<_>>();
-    types_and_idents
+    variant_to_field_kinds(variant, params)
+        .iter()
+        .map(|(ident, kind)| {
+            let mapped_ident = match kind {
+                FieldKind::Basic(_) | FieldKind::OptionBasic(_) => map_basic_ty(ident),
+                FieldKind::Node(_) => map_complex_ty(ident),
+                FieldKind::OptionNode(_) => {
+                    let mapped = map_complex_ty(ident);
+                    quote! { if let Some(#ident) = #ident { #mapped } }
+                }
+            };
+            quote! {  #mapped_ident}
+        })
+        .collect::<Vec<_>>()
+}
+/// expression converting argument of new fn or set fn to the stored value
+fn assign_expr(ident: &Ident, kind: &FieldKind) -> TokenStream {
+    match kind {
+        FieldKind::Basic(_) | FieldKind::OptionBasic(_) => ident.to_token_stream(),
+        FieldKind::Node(node_ty @ NodeTy::Param(_)) => node_sym_expr(node_ty, &quote!(#ident.as_ref())),
+        FieldKind::Node(node_ty) => node_sym_expr(node_ty, &ident.to_token_stream()),
+        FieldKind::OptionNode(_) => quote!(#ident.map(|node| node.sym)),
+    }
+}
+pub fn variants_to_assign_node_field_list(
+    variant: &Variant,
+    params: &[Ident],
+) -> Vec<proc_macro2::TokenStream> {
+    variant_to_field_kinds(variant, params)
+        .iter()
+        .map(|(ident, kind)| {
+            let node_ty = assign_expr(ident, kind);
+            quote! { #ident : #node_ty}
+        })
+        .collect::<Vec<_>>()
 }
 pub fn variants_to_assign_node_field_list_without_prefixed_ident(
     variant: &Variant,
+    params: &[Ident],
 ) -> Vec<proc_macro2::TokenStream> {
-    let types_and_idents = match &variant.fields {
-        Fields::Named(fields_named) => fields_named.named.iter(),
-        Fields::Unit => {
-            panic!("add `{{}}` to the unit variant")
-        }
-        _ => panic!("only support named fields"),
-    }
-    .map(|f| {
-        let f_ident = f
-            .ident
-            .as_ref()
-            .expect("don't support unnamed field")
-            .clone();
-        // if it's a box type we should read the first generic
-        if is_box_type(&f.ty) {
-            (get_first_generic(&f.ty).clone(), f_ident)
-        } else {
-            (f.ty.clone(), f_ident)
-        }
-    })
-    .zip(variant_to_field_ident(variant))
-    .map(|((f1, _), ident)| {
-        let node_ty = match f1.to_token_stream().to_string().as_str() {
-            x if PANIC_TY_LIST.contains(&x) => {
-                panic!("{} not supported", x)
-            }
-            x if EGGLOG_BASIC_TY_LIST.contains(&x) => ident.to_token_stream(),
-            _ => {
-                quote!(#ident.sym)
-            }
-        };
-        quote! { #node_ty}
-    })
-    .collect::<Vec<_>>();
-    types_and_idents
+    variant_to_field_kinds(variant, params)
+        .iter()
+        .map(|(ident, kind)| assign_expr(ident, kind))
+        .collect::<Vec<_>>()
 }
 pub fn variant_to_field_list_without_prefixed_ident_filter_out_basic_ty(
     variant: &Variant,
+    params: &[Ident],
 ) -> Vec<proc_macro2::TokenStream> {
-    let ty = match &variant.fields {
-        Fields::Named(fields_named) => fields_named.named.iter(),
-        Fields::Unit => {
-            panic!("add `{{}}` to the unit variant")
-        }
-        _ => panic!("only support named fields"),
-    }
-    .map(|f| {
-        let f_ident = f
-            .ident
-            .as_ref()
-            .expect("don't support unnamed field")
-            .clone();
-        // if it's a box type we should read the first generic
-        if is_box_type(&f.ty) {
-            (get_first_generic(&f.ty).clone(), f_ident)
-        } else {
-            (f.ty.clone(), f_ident)
+    variant_to_field_kinds(variant, params)
+        .iter()
+        .filter_map(|(ident, kind)| match kind {
+            FieldKind::Node(_) => Some(quote! { #ident}),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+}
+/// given variant a{ x:X, y:Option<Y>, z:Option<i64>}
+/// return vec![ y ]
+pub fn variant_to_option_node_field_list(
+    variant: &Variant,
+    params: &[Ident],
+) -> Vec<proc_macro2::TokenStream> {
+    variant_to_field_kinds(variant, params)
+        .iter()
+        .filter_map(|(ident, kind)| match kind {
+            FieldKind::OptionNode(_) => Some(quote! { #ident}),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+}
+
+/// given variant a{ x:X, y:Y}
+/// return iterator [ x, y ].iter()
+pub fn variant_to_field_ident(variant: &Variant) -> impl Iterator<Item = proc_macro2::Ident> {
+    variant_fields(variant).into_iter().map(|(f_ident, _)| f_ident)
+}
+
+/// render field bound to `x` in egglog, literals are rendered by EgglogPrim and nodes by their syms
+pub fn field_render(x: &Ident, kind: &FieldKind) -> TokenStream {
+    match kind {
+        FieldKind::Basic(_) => quote! {#x.to_egglog_lit()},
+        FieldKind::Node(_) => quote! {#x},
+        FieldKind::OptionBasic(_) | FieldKind::OptionNode(_) => {
+            let (_, some, none) = option_sort_of(kind);
+            let some_str = format!("({} {{}})", some);
+            let none_str = format!("({})", none);
+            let inner = if kind.is_basic() {
+                quote! {x.to_egglog_lit()}
+            } else {
+                quote! {x}
+            };
+            quote! {
+                match #x {
+                    Some(x) => format!(#some_str, #inner),
+                    None => #none_str.to_owned(),
+                }
+            }
         }
-    })
-    .zip(variant_to_field_ident(variant))
-    .filter_map(|((f1, _f2), ident)| {
-        let node_ty = match f1.to_token_stream().to_string().as_str() {
-            x if PANIC_TY_LIST.contains(&x) => {
-                panic!("{} not supported", x)
+    }
+}
+
+/// build argument of new fn from `args[idx]` of an egglog call
+pub fn field_from_egglog(idx: usize, kind: &FieldKind) -> TokenStream {
+    match kind {
+        FieldKind::Basic(ty) => {
+            quote! {<#ty as FromEgglog>::from_egglog(&args[#idx])}
+        }
+        FieldKind::Node(node_ty) => {
+            let node = node_ty.node(quote!(()));
+            quote! {&<#node as FromEgglog>::from_egglog(&args[#idx])}
+        }
+        FieldKind::OptionBasic(_) | FieldKind::OptionNode(_) => {
+            let (_, some, _) = option_sort_of(kind);
+            let some_str = some.to_string();
+            let (inner, as_ref) = match kind {
+                FieldKind::OptionBasic(ty) => (quote! {#ty}, quote! {}),
+                FieldKind::OptionNode(ty_ident) => (quote! {#ty_ident<T,()>}, quote! {.as_ref()}),
+                _ => unreachable!(),
+            };
+            quote! {
+                {
+                    let (head, inner) = expr_to_call(&args[#idx]);
+                    if head == #some_str {
+                        Some(<#inner as FromEgglog>::from_egglog(&inner[0]))
+                    } else {
+                        None
+                    }
+                }#as_ref
             }
-            x if EGGLOG_BASIC_TY_LIST.contains(&x) => return None,
-            _ => {
-                quote!(#ident)
+        }
+    }
+}
+
+/// whether struct form `#[egglog_ty]` wraps a single container, such as `struct Points { v: Vec<Point> }`
+pub fn is_container_struct(data_struct: &DataStruct) -> bool {
+    data_struct.fields.len() == 1 && data_struct.fields.iter().all(|f| is_container_type(&f.ty))
+}
+
+/// view product struct as the only variant of its datatype, the constructor is named after the struct
+///
+/// so that helpers of enum variants could be reused
+pub fn struct_to_variant(name: &Ident, data_struct: &DataStruct) -> Variant {
+    Variant {
+        attrs: vec![],
+        ident: name.clone(),
+        fields: data_struct.fields.clone(),
+        discriminant: None,
+    }
+}
+
+/// report all errors at once rather than the first one
+fn combine_errors(errors: impl IntoIterator<Item = syn::Error>) -> syn::Result<()> {
+    match errors.into_iter().reduce(|mut combined, error| {
+        combined.combine(error);
+        combined
+    }) {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+/// type params of generic `#[egglog_ty]`, `[E]` for `List<E>`
+pub fn ty_params(input: &DeriveInput) -> Vec<Ident> {
+    input
+        .generics
+        .type_params()
+        .map(|param| param.ident.clone())
+        .collect()
+}
+
+/// type params are egglog sorts bounded by `EgglogTy`, so bounds, defaults, lifetimes and
+/// const params are rejected
+fn check_generics(input: &DeriveInput) -> Vec<syn::Error> {
+    let mut errors = vec![];
+    for param in &input.generics.params {
+        match param {
+            syn::GenericParam::Type(param) => {
+                if !param.bounds.is_empty() || param.default.is_some() {
+                    errors.push(syn::Error::new_spanned(
+                        param,
+                        "type param of `#[egglog_ty]` is an egglog sort, bounds and defaults are not supported",
+                    ));
+                }
             }
-        };
-        Some(quote! { #node_ty})
-    })
-    .collect::<Vec<_>>();
-    ty
+            param => errors.push(syn::Error::new_spanned(
+                param,
+                "only type params are supported by generic `#[egglog_ty]`",
+            )),
+        }
+    }
+    if let Some(where_clause) = &input.generics.where_clause {
+        errors.push(syn::Error::new_spanned(
+            where_clause,
+            "where clause is not supported by `#[egglog_ty]`",
+        ));
+    }
+    errors
 }
 
-/// given variant a{ x:X, y:Y}
-/// return vec![ X, Y ]
-pub fn variant_to_tys(variant: &Variant) -> Vec<Type> {
-    let tys = match &variant.fields {
-        Fields::Named(fields_named) => fields_named.named.iter(),
-        Fields::Unit => {
-            panic!("add `{{}}` to the unit variant")
-        }
-        _ => panic!("only support named fields"),
-    }
-    .map(|f| {
-        if is_box_type(&f.ty) {
-            get_first_generic(&f.ty).clone()
-        } else {
-            f.ty.clone()
+/// whether type param occurs in field kind
+fn kind_uses_param(kind: &FieldKind, param: &Ident) -> bool {
+    fn node_uses_param(node_ty: &NodeTy, param: &Ident) -> bool {
+        match node_ty {
+            NodeTy::Param(ident) => ident == param,
+            NodeTy::Ty(_, args) => args.iter().any(|arg| node_uses_param(arg, param)),
         }
-    })
-    .collect::<Vec<_>>();
-    tys
+    }
+    matches!(kind, FieldKind::Node(node_ty) if node_uses_param(node_ty, param))
 }
 
-/// given variant a{ x:X, y:Y}
-/// return iterator [ x, y ].iter()
-pub fn variant_to_field_ident(variant: &Variant) -> impl Iterator<Item = &proc_macro2::Ident> {
-    match &variant.fields {
-        Fields::Named(fields_named) => fields_named.named.iter(),
-        Fields::Unit => {
-            panic!("add `{{}}` to the unit variant")
+/// methods of nodes, which would be shadowed by field getters of the same name
+const NODE_METHODS: &[&str] = &[
+    "new", "len", "is_empty", "commit", "stage", "succs", "succs_mut", "next_sym", "cur_sym",
+    "cur_sym_mut", "clone_dyn", "sort_name", "term", "to_egglog", "as_ref", "clone",
+    "locate_latest", "locate_next", "locate_prev",
+];
+
+/// fields get getters `{field}_sym` and `{field}_sym_mut`, which must not clash with methods of node
+fn check_getter_names(fields: &Fields) -> Vec<syn::Error> {
+    fields
+        .iter()
+        .filter_map(|f| f.ident.as_ref())
+        .filter(|ident| {
+            [format!("{}_sym", ident), format!("{}_sym_mut", ident)]
+                .iter()
+                .any(|getter| NODE_METHODS.contains(&getter.as_str()))
+        })
+        .map(|ident| {
+            syn::Error::new_spanned(
+                ident,
+                format!("getter of field `{}` clashes with a method of the node generated by `#[egglog_ty]`, rename it", ident),
+            )
+        })
+        .collect()
+}
+
+/// check every construct of `#[egglog_ty]` input before code generation, errors point at the
+/// offending item so that code generation could assume the input is supported
+pub fn check_egglog_ty(input: &DeriveInput) -> syn::Result<()> {
+    let mut errors = check_generics(input);
+    let params = ty_params(input);
+    // kinds of well formed fields, to find out unused type params
+    let mut kinds = vec![];
+    let mut check_fields = |fields: &Fields, errors: &mut Vec<syn::Error>| {
+        for f in fields {
+            match try_field_kind(&f.ty, &params) {
+                Ok(kind) => kinds.push(kind),
+                Err(e) => errors.push(e),
+            }
+        }
+    };
+    match &input.data {
+        Data::Enum(data_enum) => {
+            for variant in &data_enum.variants {
+                if let Some((_, discriminant)) = &variant.discriminant {
+                    errors.push(syn::Error::new_spanned(
+                        discriminant,
+                        "discriminant is not supported, variants are egglog constructors",
+                    ));
+                }
+                if let Err(e) = variant_meta(variant) {
+                    errors.push(e);
+                }
+                errors.extend(check_getter_names(&variant.fields));
+                check_fields(&variant.fields, &mut errors);
+            }
+        }
+        Data::Struct(data_struct) if is_container_struct(data_struct) => {
+            for f in &data_struct.fields {
+                match try_container_kind(&f.ty, &params) {
+                    Ok(Some(ContainerKind::Vec(elem) | ContainerKind::Set(elem))) => kinds.push(elem),
+                    Ok(Some(ContainerKind::Map(key, value))) => kinds.extend([key, value]),
+                    Ok(None) => {}
+                    Err(e) => errors.push(e),
+                }
+            }
+        }
+        Data::Struct(data_struct) => {
+            errors.extend(check_getter_names(&data_struct.fields));
+            check_fields(&data_struct.fields, &mut errors)
+        }
+        Data::Union(data_union) => errors.push(syn::Error::new_spanned(
+            data_union.union_token,
+            "union is not supported by `#[egglog_ty]`, use enum or struct",
+        )),
+    }
+    if errors.is_empty() {
+        // unused type param couldn't be resolved from egglog term
+        for param in &params {
+            if !kinds.iter().any(|kind| kind_uses_param(kind, param)) {
+                errors.push(syn::Error::new_spanned(
+                    param,
+                    format!("type param `{}` is not used by any field", param),
+                ));
+            }
         }
-        _ => panic!("only support named fields"),
     }
-    .map(|f| f.ident.as_ref().unwrap())
+    combine_errors(errors)
+}
+
+/// check `#[egglog_func]` input, its fields are inputs of function and should be `#[egglog_ty]` types
+pub fn check_egglog_func(input: &DeriveInput) -> syn::Result<()> {
+    let Data::Struct(data_struct) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "`#[egglog_func]` only supports struct, whose fields are inputs of function",
+        ));
+    };
+    let mut errors = vec![];
+    if !input.generics.params.is_empty() {
+        errors.push(syn::Error::new_spanned(
+            &input.generics,
+            "generic `#[egglog_func]` is not supported",
+        ));
+    }
+    errors.extend(data_struct.fields.iter().filter_map(|f| match try_field_kind(&f.ty, &[]) {
+        Ok(FieldKind::Node(node_ty)) if !is_box_type(&f.ty) && !node_ty.is_instance() => None,
+        Ok(FieldKind::Node(_)) if !is_box_type(&f.ty) => Some(syn::Error::new_spanned(
+            &f.ty,
+            "instance of generic `#[egglog_ty]` could not be input of `#[egglog_func]`, wrap it in a struct",
+        )),
+        _ => Some(syn::Error::new_spanned(
+            &f.ty,
+            "input of `#[egglog_func]` should be an `#[egglog_ty]` type",
+        )),
+    }));
+    combine_errors(errors)
+}
+
+/// EgglogTy impls of nodes referenced by field, type params are skipped as they are bounded already
+fn referenced_egglogtys(kind: &FieldKind) -> Option<(TokenStream, TokenStream, Span)> {
+    match kind {
+        FieldKind::Node(NodeTy::Param(_)) => None,
+        FieldKind::Node(node_ty) => Some((
+            node_ty.egglogty(),
+            node_ty.node(quote!(())),
+            node_ty.ident().span(),
+        )),
+        FieldKind::OptionNode(ident) => {
+            let name_egglogty = format_ident!("{}Ty", ident, span = ident.span());
+            Some((name_egglogty.to_token_stream(), quote!(#ident<T, ()>), ident.span()))
+        }
+        FieldKind::Basic(_) | FieldKind::OptionBasic(_) => None,
+    }
+}
+
+/// assert every referenced node is an `#[egglog_ty]` type whose node is bound to the same Tx
+///
+/// `XTy: EgglogTyNode<T, Node = X<T>>` bounds carry the span of field type, so a typo, a plain
+/// rust type or a node of another Tx generic is reported as a type error at the field rather
+/// than by egglog at runtime
+pub fn egglog_ty_assertions<'a>(
+    kinds: impl IntoIterator<Item = &'a FieldKind>,
+    params: &[Ident],
+) -> TokenStream {
+    let egglog_wrapper_path = egglog_wrapper_path();
+    let bounds = kinds
+        .into_iter()
+        .filter_map(referenced_egglogtys)
+        .map(|(name_egglogty, node, span)| {
+            quote_spanned! {span=> #name_egglogty: #egglog_wrapper_path::wrap::EgglogTyNode<T, Node = #node>}
+        })
+        .collect::<Vec<_>>();
+    if bounds.is_empty() {
+        return quote! {};
+    }
+    quote! {
+        const _: () = {
+            #[allow(dead_code)]
+            fn assert_fields_are_egglog_ty<
+                T: #egglog_wrapper_path::wrap::TxSgl,
+                #(#params: #egglog_wrapper_path::wrap::EgglogTyNode<T>),*
+            >() where #(#bounds),* {}
+        };
+    }
+}
+
+/// register sorts of concrete instances of generic types referenced by fields, such as `List<Ctl>`
+///
+/// instances with type params are registered by the generic type being instantiated
+pub fn instance_sort_submits<'a>(kinds: impl IntoIterator<Item = &'a FieldKind>) -> TokenStream {
+    let egglog_wrapper_path = egglog_wrapper_path();
+    let inventory_path = inventory_wrapper_path();
+    let submits = kinds.into_iter().filter_map(|kind| match kind {
+        FieldKind::Node(node_ty) if node_ty.is_instance() && !node_ty.has_param() => {
+            let name_egglogty = node_ty.egglogty();
+            Some(quote! {
+                #inventory_path::submit!{
+                    #egglog_wrapper_path::wrap::SortCollector(
+                        <#name_egglogty as #egglog_wrapper_path::wrap::EgglogTy>::collect_sort_defs
+                    )
+                }
+            })
+        }
+        _ => None,
+    });
+    quote! {#(#submits)*}
 }
@@ -1,17 +1,28 @@
-use core::panic;
 use darling::{Error, FromMeta, ast::NestedMeta};
 
 use heck::ToSnakeCase;
 use helper::*;
 use proc_macro::TokenStream;
 use proc_macro2::Ident;
-use quote::{ToTokens, format_ident, quote};
-use syn::{Data, DeriveInput, Type, parse_macro_input};
+use quote::{format_ident, quote};
+use syn::{Data, DeriveInput, parse_macro_input};
 mod helper;
 
 #[derive(Debug, FromMeta)]
 struct SceneMeta {
     output: Ident,
+    /// cost of function used by extraction
+    #[darling(default)]
+    cost: Option<u64>,
+}
+/// arguments of `#[egglog_ty(name = "geo.Point", prefix = "geo_")]`
+#[derive(Debug, Default, FromMeta)]
+#[darling(default)]
+struct TyMeta {
+    /// egglog sort name, the rust name by default
+    name: Option<String>,
+    /// prepended to constructors and syms, so that types of different crates don't collide
+    prefix: Option<String>,
 }
 #[proc_macro_attribute]
 pub fn egglog_func(attr: TokenStream, item: TokenStream) -> TokenStream {
@@ -26,9 +37,13 @@ pub fn egglog_func(attr: TokenStream, item: TokenStream) -> TokenStream {
         Ok(v) => v,
         Err(e) => return TokenStream::from(e.write_errors()),
     };
+    if let Err(e) = check_egglog_func(&input) {
+        return TokenStream::from(e.to_compile_error());
+    }
 
     let output = args.output;
-    let struct_def_expanded = match &input.data {
+    let cost_option = cost_option(args.cost);
+    match &input.data {
         Data::Struct(data_struct) => {
             let name_node = format_ident!("{}", name);
             // let derive_more_path  = derive_more_path();
@@ -55,8 +70,15 @@ pub fn egglog_func(attr: TokenStream, item: TokenStream) -> TokenStream {
             //     .collect::<Vec<_>>();
 
             let inventory_path = inventory_wrapper_path();
-            let _merge_option: proc_macro2::TokenStream = "no-merge".to_token_stream();
-            let merge_option: proc_macro2::TokenStream = "merge new".to_token_stream();
+            let egglog_wrapper_path = egglog_wrapper_path();
+            // sorts of input and output may be renamed, so the definition is rendered at runtime
+            let input_egglogtys = types
+                .iter()
+                .map(|ty| format_ident!("{}Ty", ty_name(ty)))
+                .collect::<Vec<_>>();
+            let output_egglogty = format_ident!("{}Ty", output);
+            let _merge_option = "no-merge";
+            let merge_option = "merge new";
             quote! {
                 pub struct #name_node<T>{_p:std::marker::PhantomData<T>}
                 impl<T:SingletonGetter> egglog_wrapper::wrap::EgglogFunc for #name_node<T>{
@@ -69,30 +91,62 @@ pub fn egglog_func(attr: TokenStream, item: TokenStream) -> TokenStream {
                         T::on_func_set::<#name_node<T>>(input, output.as_ref());
                     }
                 }
-                impl<'a, R:RxSgl> #name_node<R> where R:RxSgl{
+                impl<'a, T:SingletonGetter> #name_node<T>{
+                    /// call of this function as a term of facts, such as `check!(F::apply((&a,)) == b)`
+                    pub fn apply(input: (#(&'a dyn AsRef<#types<T,()>>,)*)) -> #egglog_wrapper_path::wrap::FuncApp<#name_node<T>>{
+                        let input_nodes = #egglog_wrapper_path::wrap::EgglogFuncInputsRef::as_nodes(&input);
+                        #egglog_wrapper_path::wrap::FuncApp::new(&input_nodes)
+                    }
+                }
+                impl<'a, R:RxSgl + TxSgl> #name_node<R> where R:RxSgl{
+                    /// read output of this function, nodes of the extracted term are created by R
                     pub fn get(input: (#(&'a dyn AsRef<#types<R,()>>,)*)) -> #output<R,()>{
-                        R::on_func_get::<#name_node<R>>(input).as_ref().clone()
+                        R::on_func_get::<#name_node<R>>(input)
+                    }
+                    /// read at most max_size rows of this function
+                    pub fn get_all(max_size: Option<usize>) -> Vec<((#(#types<R,()>,)*), #output<R,()>)>{
+                        R::on_funcs_get::<#name_node<R>>(max_size)
+                    }
+                    /// read at most max_size rows of this function after skipping the first offset rows,
+                    /// the table is read again for every page
+                    pub fn get_page(offset: usize, max_size: Option<usize>) -> Vec<((#(#types<R,()>,)*), #output<R,()>)>{
+                        R::on_funcs_get_page::<#name_node<R>>(offset, max_size)
+                    }
+                    /// iterate all rows of this function, the table is read page by page and rows are
+                    /// typed as they are iterated
+                    pub fn iter_all() -> #egglog_wrapper_path::wrap::FuncRows<#name_node<R>, R>{
+                        #egglog_wrapper_path::wrap::FuncRows::default()
                     }
                 }
                 #inventory_path::submit!{
-                    FuncSort(
-                        concat!( "(", stringify!( function #name_node (#(#types )*)  #output:),#merge_option,")")
+                    #egglog_wrapper_path::wrap::FuncSort(
+                        stringify!(#name_node),
+                        concat!(module_path!(), "::", stringify!(#name_node)),
+                        || {
+                            use #egglog_wrapper_path::wrap::EgglogTy;
+                            let inputs: Vec<String> = vec![#(<#input_egglogtys as EgglogTy>::sort_name()),*];
+                            format!(
+                                "(function {} ({}) {}{} :{})",
+                                stringify!(#name_node),
+                                inputs.join(" "),
+                                <#output_egglogty as EgglogTy>::sort_name(),
+                                #cost_option,
+                                #merge_option
+                            )
+                        }
                     )
                 }
             }.into()
         }
-        _ => {
-            panic!()
-        }
-    };
-    struct_def_expanded
+        _ => unreachable!("checked by check_egglog_func"),
+    }
 }
 
 /// generate `egglog` language from `rust native structure`   
 ///
 /// # Example:  
 ///     
-/// ```
+/// ```ignore
 /// #[allow(unused)]
 /// #[derive(Debug, Clone, EgglogTy)]
 /// enum Duration {
@@ -107,7 +161,7 @@ pub fn egglog_func(attr: TokenStream, item: TokenStream) -> TokenStream {
 /// is transformed to
 ///
 ///
-/// ```
+/// ```ignore
 /// #[derive(Debug, Clone, ::derive_more::Deref)]
 /// pub struct DurationNode {
 ///     ty: _DurationNode,
@@ -134,244 +188,724 @@ pub fn egglog_func(attr: TokenStream, item: TokenStream) -> TokenStream {
 ///
 /// also there is a type def statement generated and specialized new function
 ///
+/// `#[egglog_ty(name = "geo.Point", prefix = "geo_")]` renames the sort and prefixes constructors,
+/// a single constructor is renamed by `#[egglog(name = "Origin")]` on its variant. syms of nodes
+/// are named after the sort, such as `geo.point0`.
+/// names conflicting with other types are reported when type defs are collected at startup
 ///
+/// `#[egglog(cost = 10)]` on variant sets `:cost` of its constructor, which is used by extraction
 #[proc_macro_attribute]
-pub fn egglog_ty(_attr: TokenStream, item: TokenStream) -> TokenStream {
+pub fn egglog_ty(attr: TokenStream, item: TokenStream) -> TokenStream {
     let input = parse_macro_input!(item as DeriveInput);
     let name = &input.ident;
+    let attr = proc_macro2::TokenStream::from(attr);
+    let attr_args = match NestedMeta::parse_meta_list(attr.clone()) {
+        Ok(v) => v,
+        Err(e) => return TokenStream::from(Error::from(e).write_errors()),
+    };
+    let args = match TyMeta::from_list(&attr_args) {
+        Ok(v) => v,
+        Err(e) => return TokenStream::from(e.write_errors()),
+    };
+    let names_checked = [&args.name, &args.prefix]
+        .into_iter()
+        .flatten()
+        .try_for_each(|name| check_egglog_name(name, &attr));
+    if let Err(e) = names_checked.and_then(|_| check_egglog_ty(&input)) {
+        return TokenStream::from(e.to_compile_error());
+    }
 
-    let name_lowercase = format_ident!("{}", name.to_string().to_lowercase());
+    let sort_base = args.name.unwrap_or_else(|| name.to_string());
+    let prefix = args.prefix.unwrap_or_default();
+    // syms are named after the sort rather than the rust ident, so types renamed apart don't share them
+    let sym_prefix = sort_base.to_lowercase();
     let name_egglogty_impl = format_ident!("{}Ty", name);
+    let name_counter = format_ident!("{}_COUNTER", name.to_string().to_uppercase());
     let egglog_wrapper_path = egglog_wrapper_path();
     let inventory_path = inventory_wrapper_path();
 
-    let type_def_expanded = match &input.data {
+    // type params of generic type are egglog sorts, they follow T in node type such as `List<T, E, V>`
+    let params = ty_params(&input);
+    let ty_generics = if params.is_empty() {
+        quote! {}
+    } else {
+        quote! {<#(#params),*>}
+    };
+    let node_params = quote! {#(#params,)*};
+    let impl_params = quote! {#(#params: EgglogTy,)*};
+    let node_impl_params = quote! {#(#params: EgglogTyNode<T>,)*};
+    let egglogty_decl = if params.is_empty() {
+        quote! {
+            #[derive(Clone,Debug)]
+            pub struct #name_egglogty_impl;
+        }
+    } else {
+        quote! {
+            #[derive(Clone,Debug)]
+            pub struct #name_egglogty_impl #ty_generics (std::marker::PhantomData<(#node_params)>);
+        }
+    };
+    // instances of generic type share a counter, their syms never collide
+    let counter_def = if params.is_empty() {
+        quote! {static #name_counter: TyCounter<#name_egglogty_impl> = TyCounter::new();}
+    } else {
+        quote! {static #name_counter: GenericTyCounter = GenericTyCounter::new(#sym_prefix);}
+    };
+    // sort of generic type is only a template, sorts of its instances are rendered by instance_def
+    let field_sort = |kind: &FieldKind| {
+        if params.is_empty() {
+            kind_sort(kind)
+        } else {
+            kind_sort_template(kind)
+        }
+    };
+    let fields_sort_expr = |kinds: &[(Ident, FieldKind)]| {
+        let sorts = kinds.iter().map(|(_, kind)| kind_sort_expr(kind));
+        quote! {{
+            let fields: Vec<String> = vec![#(#sorts),*];
+            fields.join(" ")
+        }}
+    };
+
+    let (kinds, sort_def, ctors, instance_def) = match &input.data {
         Data::Enum(data_enum) => {
-            let variants_egglog = data_enum
+            let variants_kinds = data_enum
                 .variants
                 .iter()
-                .map(|variant| {
-                    let tys = variant_to_tys(&variant);
-                    let variant_name = &variant.ident;
-                    quote! {  (#variant_name #(#tys )* )}
+                .map(|variant| (variant, variant_to_field_kinds(variant, &params)))
+                .collect::<Vec<_>>();
+            let costs = variants_kinds
+                .iter()
+                .map(|(variant, _)| {
+                    let cost = variant_meta(variant).expect("checked by check_egglog_ty").cost;
+                    cost_option(cost)
                 })
                 .collect::<Vec<_>>();
-            let expanded = quote! {
-                use #egglog_wrapper_path::wrap::*;
-                impl EgglogTy for #name_egglogty_impl {
-                    const TY_NAME:&'static str = stringify!(#name);
-                    const TY_NAME_LOWER:&'static str = stringify!(#name_lowercase);
-                    const SORT_DEF: TySort=
-                        TySort(stringify!(
-                            (#name
-                                #(#variants_egglog)*
-                            )
-                        ));
-                }
-                #inventory_path::submit!{#name_egglogty_impl::SORT_DEF}
+            let variants_egglog = variants_kinds.iter().zip(&costs).map(|((variant, kinds), cost)| {
+                let variant_name = &variant.ident;
+                let tys = kinds.iter().map(|(_, kind)| field_sort(kind));
+                // `:cost` is spliced as is, tokens would be printed as `: cost` by stringify
+                let cost: proc_macro2::TokenStream = cost.parse().expect("cost option is valid tokens");
+                quote! {  (#variant_name #(#tys )* #cost)}
+            });
+            let sort_def = quote! {
+                (#name
+                    #(#variants_egglog)*
+                )
             };
-            expanded
+            let ctors = variants_kinds
+                .iter()
+                .map(|(variant, _)| ctor_name(variant, &prefix, &params))
+                .collect::<Vec<_>>();
+            let variants_def = variants_kinds.iter().zip(&costs).enumerate().map(|(idx, ((_, kinds), cost))| {
+                let fields = fields_sort_expr(kinds);
+                quote! {format!("({} {}{})", ctors[#idx], #fields, #cost)}
+            });
+            let instance_def = quote! {{
+                let variants: Vec<String> = vec![#(#variants_def),*];
+                format!("({} {})", sort, variants.join(" "))
+            }};
+            let kinds = variants_kinds
+                .into_iter()
+                .flat_map(|(_, kinds)| kinds)
+                .map(|(_, kind)| kind)
+                .collect::<Vec<_>>();
+            (kinds, sort_def, ctors, instance_def)
+        }
+        Data::Struct(data_struct) if !is_container_struct(data_struct) => {
+            // product struct is a datatype with a single constructor named after the struct
+            let variant = struct_to_variant(name, data_struct);
+            let kinds = variant_to_field_kinds(&variant, &params);
+            let tys = kinds.iter().map(|(_, kind)| field_sort(kind));
+            let sort_def = quote! {(#name (#name #(#tys )*))};
+            let fields = fields_sort_expr(&kinds);
+            let ctors = vec![ctor_name(&variant, &prefix, &params)];
+            let instance_def = quote! {format!("({} ({} {}))", sort, ctors[0], #fields)};
+            (kinds.into_iter().map(|(_, kind)| kind).collect(), sort_def, ctors, instance_def)
         }
         Data::Struct(data_struct) => {
             // process (sort A (Vec M))  such things ..
             let f = data_struct
                 .fields
                 .iter()
-                .nth(0)
-                .expect("Struct should only have one Vec field");
-            let first_generic = get_first_generic(&f.ty);
-            if is_vec_type(&f.ty) {
-                let vec_expanded = quote! {
-                    impl #egglog_wrapper_path::wrap::EgglogTy for #name_egglogty_impl {
-                        const TY_NAME:&'static str = stringify!(#name);
-                        const TY_NAME_LOWER:&'static str = stringify!(#name_lowercase);
-                        const SORT_DEF: TySort=
-                            TySort(stringify!(
-                                (sort #name (Vec #first_generic))
-                            ));
-                    }
-                    #inventory_path::submit!{#name_egglogty_impl::SORT_DEF}
-                };
-                vec_expanded
-            } else {
-                panic!("only support Vec for struct")
+                .next()
+                .expect("Struct should only have one container field");
+            let container = container_kind(&f.ty, &params).expect("field of container struct is a container");
+            let container_sort = container.sort(field_sort);
+            let sort_def = quote! {(sort #name #container_sort)};
+            let container_sort_expr = container.sort_expr();
+            let instance_def = quote! {format!("(sort {} {})", sort, #container_sort_expr)};
+            let kinds = match container {
+                ContainerKind::Vec(elem) | ContainerKind::Set(elem) => vec![elem],
+                ContainerKind::Map(key, value) => vec![key, value],
+            };
+            (kinds, sort_def, vec![], instance_def)
+        }
+        Data::Union(_) => unreachable!("union is rejected by check_egglog_ty"),
+    };
+    // option datatypes may be collected by several types, they are deduplicated when collected
+    let option_sort_collects = kinds
+        .iter()
+        .filter(|kind| matches!(kind, FieldKind::OptionBasic(_) | FieldKind::OptionNode(_)))
+        .map(option_sort_collect)
+        .collect::<Vec<_>>();
+    let assertions = egglog_ty_assertions(&kinds, &params);
+    let instance_submits = instance_sort_submits(&kinds);
+    // instances it depends on are collected as well, the reserved entry stops recursive instances
+    let depended_instances = kinds
+        .iter()
+        .filter_map(|kind| match kind {
+            FieldKind::Node(node_ty) if node_ty.is_instance() => Some(node_ty.egglogty()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let collect_sort_defs = quote! {
+        fn collect_sort_defs(defs: &mut SortDefs) {
+            let sort = Self::sort_name();
+            if !defs.reserve(&sort, concat!(module_path!(), "::", stringify!(#name))) {
+                return;
+            }
+            defs.reserve_sym_prefix(Self::TY_NAME_LOWER, concat!(module_path!(), "::", stringify!(#name)));
+            #(<#params as EgglogTy>::collect_sort_defs(defs);)*
+            #(<#depended_instances as EgglogTy>::collect_sort_defs(defs);)*
+            #(#option_sort_collects)*
+            let ctors: Vec<String> = vec![#(#ctors.to_owned()),*];
+            let def = #instance_def;
+            defs.insert(&sort, ctors, def);
+        }
+    };
+    let egglogty_impl = if params.is_empty() {
+        quote! {
+            impl EgglogTy for #name_egglogty_impl {
+                const TY_NAME:&'static str = #sort_base;
+                const TY_NAME_LOWER:&'static str = #sym_prefix;
+                const SORT_DEF: TySort=
+                    TySort(stringify!(#sort_def));
+                #collect_sort_defs
+            }
+            #inventory_path::submit!{SortCollector(#name_egglogty_impl::collect_sort_defs)}
+        }
+    } else {
+        let sort_name = mangled_name(&sort_base, &params);
+        quote! {
+            impl<#impl_params> EgglogTy for #name_egglogty_impl #ty_generics {
+                const TY_NAME:&'static str = #sort_base;
+                const TY_NAME_LOWER:&'static str = #sym_prefix;
+                const SORT_DEF: TySort=
+                    TySort(stringify!(#sort_def));
+                fn sort_name() -> String {
+                    #sort_name
+                }
+                #collect_sort_defs
+            }
+        }
+    };
+    let type_def_expanded = quote! {
+        use #egglog_wrapper_path::wrap::*;
+        #assertions
+        #instance_submits
+        #egglogty_decl
+        #egglogty_impl
+        impl<T: TxSgl, #node_impl_params> EgglogTyNode<T> for #name_egglogty_impl #ty_generics {
+            type Node = #name<T, #node_params ()>;
+        }
+        impl<T: TxSgl, #node_impl_params> EgglogBinding for #name<T, #node_params ()> {
+            fn sort_name() -> String {
+                <#name_egglogty_impl #ty_generics as EgglogTy>::sort_name()
             }
         }
-        _ => panic!("only support enum"),
     };
     let struct_def_expanded = match &input.data {
+        // transform struct A{ a:i32, b:B }
+        // into struct AInner { a:i32, b:BSym } stored by node like a single variant enum
+        Data::Struct(data_struct) if !is_container_struct(data_struct) => {
+            let name_node_alias = format_ident!("{}NodeAlias", name);
+            let name_node = format_ident!("{}", name);
+            let name_inner = format_ident!("{}Inner", name);
+            let variant = struct_to_variant(name, data_struct);
+
+            let types_and_idents = variants_to_sym_typed_ident_list(&variant, &params);
+            let field_idents = variant_to_field_ident(&variant).collect::<Vec<_>>();
+            let ref_node_list = variant_to_ref_node_list(&variant, &params);
+            let assign_node_field_list = variants_to_assign_node_field_list(&variant, &params);
+            let kinds = variant_to_field_kinds(&variant, &params);
+            let arg_count = kinds.len();
+
+            let rendered_fields = kinds.iter().map(|(x, kind)| field_render(x, kind)).collect::<Vec<_>>();
+            // constructor is named after the struct, which is mangled for instances of generic type
+            let ctor = ctor_name(&variant, &prefix, &params);
+            let format_str = format!("(let {{}} ({{}}{}))", " {}".repeat(arg_count));
+            let from_egglog_args = kinds
+                .iter()
+                .enumerate()
+                .map(|(idx, (_, kind))| field_from_egglog(idx, kind));
+
+            let set_fns = variants_to_assign_node_field_list_without_prefixed_ident(&variant, &params)
+                .into_iter()
+                .zip(ref_node_list.iter().zip(field_idents.iter()))
+                .map(|(assign_node_field, (ref_node, field_ident))| {
+                    let set_fn_name = set_fn_name(field_ident);
+                    quote! {
+                        /// set fn of node, firstly update the sym version and specified field and then informs rx what happen on this node
+                        /// rx's behavior depends on whether version control is enabled
+                        pub fn #set_fn_name(&mut self,#ref_node) -> &mut Self{
+                            self.node.ty.#field_ident = #assign_node_field;
+                            T::on_set(self);
+                            self
+                        }
+                    }
+                });
+            // same accessors as fields of enum variants
+            let get_fns = variants_to_sym_type_list(&variant, &params)
+                .into_iter()
+                .zip(field_idents.iter())
+                .map(|(sym, field_ident)| {
+                    let get_fn_name = format_ident!("{}_sym", field_ident);
+                    let get_mut_fn_name = format_ident!("{}_sym_mut", field_ident);
+                    quote! {
+                        /// value of literal field, or sym of node field
+                        pub fn #get_fn_name(&self) -> #sym{
+                            self.node.ty.#field_ident.clone()
+                        }
+                        pub fn #get_mut_fn_name(&mut self) -> &mut #sym{
+                            &mut self.node.ty.#field_ident
+                        }
+                    }
+                });
+            let locate_fns = [quote!(locate_latest), quote!(locate_next), quote!(locate_prev)]
+                .into_iter()
+                .zip([quote!(set_latest), quote!(set_next), quote!(set_prev)])
+                .map(|(locate_fn, set_fn)| {
+                    let mapped_idents = variant_to_mapped_ident_list(
+                        &variant,
+                        &params,
+                        |_| quote! {},
+                        |x| quote! { T::#set_fn(#x.erase_mut()); },
+                    );
+                    quote! {
+                        fn #locate_fn(&mut self) {
+                            #[allow(unused_variables)]
+                            let #name_inner { #(#field_idents),* } = &mut self.node.ty;
+                            T::#set_fn(self.node.sym.erase_mut());
+                            #(#mapped_idents)*
+                        }
+                    }
+                });
+            let vec_needed_syms = variant_to_field_list_without_prefixed_ident_filter_out_basic_ty(&variant, &params);
+            let option_needed_syms = variant_to_option_node_field_list(&variant, &params);
+
+            quote! {
+                pub type #name_node_alias<T,#node_params V> = #egglog_wrapper_path::wrap::Node<#name_egglogty_impl #ty_generics,T,#name_inner #ty_generics,V>;
+                #[derive(::derive_more::DerefMut,::derive_more::Deref)]
+                pub struct #name_node<T: SingletonGetter, #impl_params V: EgglogEnumVariantTy=()> {
+                    node:#name_node_alias<T,#node_params V>
+                }
+                #[allow(unused)]
+                #[derive(Clone,Debug)]
+                pub struct #name_inner #ty_generics {
+                    #(#types_and_idents),*
+                }
+                const _:() = {
+                    use std::marker::PhantomData;
+                    use #egglog_wrapper_path::wrap::*;
+                    impl<#impl_params> NodeInner<#name_egglogty_impl #ty_generics> for #name_inner #ty_generics {}
+                    #counter_def
+                    impl<T:TxSgl, #node_impl_params> #name_node<T,#node_params ()> {
+                        pub fn new(#(#ref_node_list),*) -> #name_node<T,#node_params ()>{
+                            let ty = #name_inner {#(#assign_node_field_list),*  };
+                            let node = Node { ty, sym: #name_counter.next_sym(), _p:PhantomData, _s:PhantomData};
+                            let node = #name_node {node};
+                            T::on_new(&node);
+                            node
+                        }
+                        #(#set_fns)*
+                        #(#get_fns)*
+                    }
+                    impl<T:TxSgl+ VersionCtlSgl + TxCommitSgl, #impl_params S: EgglogEnumVariantTy> Commit for #name_node<T,#node_params S>
+                    where
+                        #name_node<T, #node_params S>: EgglogNode
+                    {
+                        fn commit(&self) {
+                            T::on_commit(self);
+                        }
+                        fn stage(&self) {
+                            T::on_stage(self);
+                        }
+                    }
+                    impl<T:SingletonGetter, #impl_params> EgglogNode for #name_node<T,#node_params ()> {
+                        fn succs_mut(&mut self) -> Vec<&mut Sym>{
+                            #[allow(unused_variables)]
+                            let #name_inner { #(#field_idents),* } = &mut self.node.ty;
+                            #[allow(unused_mut)]
+                            let mut succs = vec![#(#vec_needed_syms.erase_mut()),*];
+                            #(if let Some(x) = #option_needed_syms { succs.push(x.erase_mut()) })*
+                            succs
+                        }
+                        fn succs(&self) -> Vec<Sym>{
+                            #[allow(unused_variables)]
+                            let #name_inner { #(#field_idents),* } = &self.node.ty;
+                            #[allow(unused_mut)]
+                            let mut succs = vec![#((#vec_needed_syms).erase()),*];
+                            #(if let Some(x) = #option_needed_syms { succs.push(x.erase()) })*
+                            succs
+                        }
+                        fn next_sym(&mut self) -> Sym{
+                            let next_sym = #name_counter.next_sym();
+                            self.node.sym = next_sym;
+                            next_sym.erase()
+                        }
+                        fn cur_sym(&self) -> Sym{
+                            self.node.sym.erase()
+                        }
+                        fn cur_sym_mut(&mut self) -> &mut Sym{
+                            self.node.sym.erase_mut()
+                        }
+                        fn clone_dyn(&self) -> Box<dyn EgglogNode>{
+                            Box::new(self.clone())
+                        }
+                        fn sort_name(&self) -> String{
+                            <#name_egglogty_impl #ty_generics as EgglogTy>::sort_name()
+                        }
+                        fn ctor_fields(&self) -> Option<(String, Vec<String>)>{
+                            let #name_inner { #(#field_idents),* } = &self.node.ty;
+                            Some((format!("{}", #ctor), vec![#(format!("{}", #rendered_fields)),*]))
+                        }
+                    }
+                    impl<T: SingletonGetter, #impl_params V: EgglogEnumVariantTy> EgglogTerm for #name_node<T, #node_params V> {
+                        type Sort = #name_node<T, #node_params ()>;
+                        fn term(&self) -> String {
+                            self.node.sym.to_string()
+                        }
+                    }
+                    impl<T: SingletonGetter, #impl_params V: EgglogEnumVariantTy> AsRef<#name_node<T, #node_params ()>> for #name_node<T, #node_params V> {
+                        fn as_ref(&self) -> &#name_node<T, #node_params ()> {
+                            unsafe {
+                                &*(self as *const #name_node<T,#node_params V> as *const #name_node<T,#node_params ()>)
+                            }
+                        }
+                    }
+                    impl<T:SingletonGetter, #impl_params V:EgglogEnumVariantTy > Clone for #name_node<T,#node_params V> {
+                        fn clone(&self) -> Self {
+                            Self { node: Node { ty: self.node.ty.clone(), sym: self.node.sym.clone(), _p: PhantomData, _s: PhantomData }  }
+                        }
+                    }
+                    impl<T:SingletonGetter, #impl_params V:EgglogEnumVariantTy> ToEgglog for #name_node<T,#node_params V>
+                    where #name_node<T,#node_params V>: EgglogNode{
+                        fn to_egglog(&self) -> String{
+                            let #name_inner { #(#field_idents),* } = &self.node.ty;
+                            format!(#format_str ,self.node.sym, #ctor, #(#rendered_fields),*)
+                        }
+                    }
+                    impl<T:TxSgl, #node_impl_params> FromEgglog for #name_node<T,#node_params ()> {
+                        /// create nodes of the term by `new` fn, so they are also received by T
+                        fn from_egglog(expr: &EgglogExpr) -> Self {
+                            let (head, args) = expr_to_call(expr);
+                            let ctor = #ctor;
+                            assert_eq!(head, ctor, "unknown constructor {} of {}", head, stringify!(#name_node));
+                            assert_eq!(args.len(), #arg_count, "wrong number of args of {}", ctor);
+                            Self::new(#(#from_egglog_args),*)
+                        }
+                    }
+                    impl<T:TxSgl + VersionCtlSgl, #impl_params V:EgglogEnumVariantTy> LocateVersion for #name_node<T,#node_params V>
+                    where #name_node<T,#node_params V> : EgglogNode
+                    {
+                        #(#locate_fns)*
+                    }
+                };
+            }
+        }
         Data::Struct(data_struct) => {
             // process (sort A (Vec M))  such things ..
             let name_node_alias = format_ident!("{}NodeAlias", name);
             let name_node = format_ident!("{}", name);
             let name_inner = format_ident!("{}Inner", name);
             // let name_snakecase = format_ident!("{}",name.to_string().to_snake_case());
-            let name_counter = format_ident!("{}_COUNTER", name.to_string().to_uppercase());
             // let derive_more_path  = derive_more_path();
             let f = data_struct
                 .fields
                 .iter()
-                .nth(0)
-                .expect("Struct should only have one Vec field");
-            let field_name = &f.ident.as_ref().unwrap();
-            let first_generic = get_first_generic(&f.ty);
-            // let field_sym_ty = get_sym_type(first_generic);
-            let (field_node_ty, is_basic_ty) =
-                match first_generic.to_token_stream().to_string().as_str() {
-                    x if PANIC_TY_LIST.contains(&x) => {
-                        panic!("{} not supported", x)
-                    }
-                    x if EGGLOG_BASIC_TY_LIST.contains(&x) => {
-                        (first_generic.to_token_stream(), true)
-                    }
-                    _ => {
-                        let first_generic_ident = match &first_generic {
-                            syn::Type::Path(type_path) => {
-                                type_path
-                                    .path
-                                    .segments
-                                    .last()
-                                    .expect("impossible")
-                                    .clone()
-                                    .ident
+                .next()
+                .expect("Struct should only have one container field");
+            // tuple struct such as `struct Points(Vec<Point>)` takes `v` as param of new fn
+            let field_name = f.ident.clone().unwrap_or_else(|| format_ident!("v"));
+            let container = container_kind(&f.ty, &params).expect("field of container struct is a container");
+            // elements are stored in a Vec whatever the container is, egglog takes care of set semantics
+            let (elem_store_ty, elem_param_ty) = match &container {
+                ContainerKind::Vec(elem) | ContainerKind::Set(elem) => {
+                    (elem.elem_store_ty(), elem.elem_param_ty())
+                }
+                ContainerKind::Map(key, value) => {
+                    let (key_store, value_store) = (key.elem_store_ty(), value.elem_store_ty());
+                    let (key_param, value_param) = (key.elem_param_ty(), value.elem_param_ty());
+                    (quote!((#key_store, #value_store)), quote!((#key_param, #value_param)))
+                }
+            };
+            let (store_expr, (succ_pushes_mut, succ_pushes), render, from_egglog) = match &container {
+                ContainerKind::Vec(elem) | ContainerKind::Set(elem) => {
+                    let (of, empty) = match &container {
+                        ContainerKind::Vec(_) => ("vec-of", "vec-empty"),
+                        _ => ("set-of", "set-empty"),
+                    };
+                    let item = quote!(item);
+                    let store = elem.elem_store_expr(&item);
+                    let rendered = elem.elem_render(&item);
+                    let value_ty = elem.elem_value_ty();
+                    let to_param = elem.elem_value_to_param(&item);
+                    let succ_push = |erase: proc_macro2::TokenStream| match elem {
+                        FieldKind::Node(_) => quote!(succs.push(item.#erase());),
+                        _ => quote!(let _ = item;),
+                    };
+                    let (succ_push_mut, succ_push) = (succ_push(quote!(erase_mut)), succ_push(quote!(erase)));
+                    (
+                        quote!(|item| #store),
+                        (quote!(|item| { #succ_push_mut }), quote!(|item| { #succ_push })),
+                        quote! {
+                            format!("(let {} ({} {}))", self.cur_sym(), #of,
+                                self.node.ty.v.iter().map(|item| #rendered).collect::<Vec<_>>().join(" "))
+                        },
+                        quote! {
+                            let values = container_elements(expr, #of, #empty).iter()
+                                .map(|item| <#value_ty as FromEgglog>::from_egglog(item))
+                                .collect::<Vec<_>>();
+                            Self::new(values.iter().map(|item| #to_param).collect())
+                        },
+                    )
+                }
+                ContainerKind::Map(key, value) => {
+                    let (k, v) = (quote!(key), quote!(value));
+                    let (key_store, value_store) = (key.elem_store_expr(&k), value.elem_store_expr(&v));
+                    let (key_rendered, value_rendered) = (key.elem_render(&k), value.elem_render(&v));
+                    let (key_value_ty, value_value_ty) = (key.elem_value_ty(), value.elem_value_ty());
+                    let (key_to_param, value_to_param) =
+                        (key.elem_value_to_param(&k), value.elem_value_to_param(&v));
+                    let succ_push = |elem: &FieldKind, e: &proc_macro2::TokenStream, erase: proc_macro2::TokenStream| {
+                        match elem {
+                            FieldKind::Node(_) => quote!(succs.push(#e.#erase());),
+                            _ => quote!(let _ = #e;),
+                        }
+                    };
+                    let (key_push_mut, value_push_mut) =
+                        (succ_push(key, &k, quote!(erase_mut)), succ_push(value, &v, quote!(erase_mut)));
+                    let (key_push, value_push) =
+                        (succ_push(key, &k, quote!(erase)), succ_push(value, &v, quote!(erase)));
+                    (
+                        quote!(|(key, value)| (#key_store, #value_store)),
+                        (
+                            quote!(|(key, value)| { #key_push_mut #value_push_mut }),
+                            quote!(|(key, value)| { #key_push #value_push }),
+                        ),
+                        quote! {
+                            format!("(let {} {})", self.cur_sym(),
+                                self.node.ty.v.iter().fold("(map-empty)".to_owned(), |map, (key, value)| {
+                                    format!("(map-insert {} {} {})", map, #key_rendered, #value_rendered)
+                                }))
+                        },
+                        quote! {
+                            let values = map_entries(expr).into_iter()
+                                .map(|(key, value)| (
+                                    <#key_value_ty as FromEgglog>::from_egglog(key),
+                                    <#value_value_ty as FromEgglog>::from_egglog(value),
+                                ))
+                                .collect::<Vec<_>>();
+                            Self::new(values.iter().map(|(key, value)| (#key_to_param, #value_to_param)).collect())
+                        },
+                    )
+                }
+            };
+            // fields of map node are the map without its last entry and the last entry, as in its term
+            let ctor_fields = match &container {
+                ContainerKind::Vec(elem) | ContainerKind::Set(elem) => {
+                    let of = match &container {
+                        ContainerKind::Vec(_) => "vec-of",
+                        _ => "set-of",
+                    };
+                    let rendered = elem.elem_render(&quote!(item));
+                    quote! {
+                        Some((#of.to_owned(), self.node.ty.v.iter().map(|item| format!("{}", #rendered)).collect()))
+                    }
+                }
+                ContainerKind::Map(key, value) => {
+                    let (key_rendered, value_rendered) = (key.elem_render(&quote!(key)), value.elem_render(&quote!(value)));
+                    quote! {
+                        let entries = self.node.ty.v.iter()
+                            .map(|(key, value)| (format!("{}", #key_rendered), format!("{}", #value_rendered)))
+                            .collect::<Vec<_>>();
+                        match entries.split_last() {
+                            None => Some(("map-empty".to_owned(), vec![])),
+                            Some(((key, value), rest)) => {
+                                let map = rest.iter().fold("(map-empty)".to_owned(), |map, (key, value)| {
+                                    format!("(map-insert {} {} {})", map, key, value)
+                                });
+                                Some(("map-insert".to_owned(), vec![map, key.clone(), value.clone()]))
                             }
-                            _ => panic!(
-                                "{} type should be simple path",
-                                first_generic.to_token_stream().to_string()
-                            ),
-                        };
-                        let _first_generic = format_ident!("{}", first_generic_ident);
-                        // postfix_type(&first_generic,"Node",Some("T")
-                        (quote!(dyn AsRef<#_first_generic<T, ()>>), false)
+                        }
                     }
-                };
-            let to_egglog_impl = if is_basic_ty {
-                quote! {
-                    impl<T:SingletonGetter, V:EgglogEnumVariantTy> ToEgglog for #name_node<T,V>
-                    where #name_node<T,V>: EgglogNode{
-                        fn to_egglog(&self) -> String{
-                            format!("(let {} (vec-of {}))",self.node.sym,self.node.ty.v.iter_mut().fold("".to_owned(), |s,item| s+ item.as_str()+" " ))
+                }
+            };
+            // Vec nodes could be edited like enum nodes, edits are versioned once staged
+            let vec_edit_fns = match &container {
+                ContainerKind::Vec(elem) => {
+                    let item = quote!(item);
+                    let store = elem.elem_store_expr(&item);
+                    quote! {
+                        /// append item, then informs rx what happen on this node
+                        pub fn push(&mut self, item: #elem_param_ty) -> &mut Self {
+                            self.node.ty.v.push(#store);
+                            T::on_set(self);
+                            self
+                        }
+                        /// insert item at idx, then informs rx what happen on this node
+                        pub fn insert(&mut self, idx: usize, item: #elem_param_ty) -> &mut Self {
+                            self.node.ty.v.insert(idx, #store);
+                            T::on_set(self);
+                            self
+                        }
+                        /// remove item at idx, then informs rx what happen on this node
+                        pub fn remove(&mut self, idx: usize) -> &mut Self {
+                            self.node.ty.v.remove(idx);
+                            T::on_set(self);
+                            self
+                        }
+                        /// replace item at idx, then informs rx what happen on this node
+                        pub fn replace(&mut self, idx: usize, item: #elem_param_ty) -> &mut Self {
+                            self.node.ty.v[idx] = #store;
+                            T::on_set(self);
+                            self
+                        }
+                        /// replace all items, then informs rx what happen on this node
+                        pub fn set_all(&mut self, items: Vec<#elem_param_ty>) -> &mut Self {
+                            self.node.ty.v = items.into_iter().map(|item| #store).collect();
+                            T::on_set(self);
+                            self
+                        }
+                        pub fn len(&self) -> usize {
+                            self.node.ty.v.len()
+                        }
+                        pub fn is_empty(&self) -> bool {
+                            self.node.ty.v.is_empty()
                         }
                     }
                 }
-            } else {
-                quote! {
-                    impl<T:SingletonGetter, V:EgglogEnumVariantTy> ToEgglog for #name_node<T,V>
-                    where #name_node<T,V>: EgglogNode{
+                _ => quote! {},
+            };
+            let container_expanded = quote! {
+                pub type #name_node_alias<T,#node_params V> = #egglog_wrapper_path::wrap::Node<#name_egglogty_impl #ty_generics,T,#name_inner #ty_generics,V>;
+                #[derive(::derive_more::DerefMut,::derive_more::Deref)]
+                pub struct #name_node<T: SingletonGetter, #impl_params V: EgglogEnumVariantTy=()> {
+                    node:#name_node_alias<T,#node_params V>
+                }
+                #[derive(Clone,Debug)]
+                pub struct #name_inner #ty_generics {
+                    v:Vec<#elem_store_ty>
+                }
+                const _:() = {
+                    use #egglog_wrapper_path::wrap::*;
+                    impl<#impl_params> NodeInner<#name_egglogty_impl #ty_generics> for #name_inner #ty_generics {}
+                    use std::marker::PhantomData;
+                    #counter_def
+                    impl<T:TxSgl, #node_impl_params> #name_node<T,#node_params ()> {
+                        pub fn new(#field_name:Vec<#elem_param_ty>) -> #name_node<T,#node_params ()>{
+                            let #field_name = #field_name.into_iter().map(#store_expr).collect();
+                            let node = Node{ ty: #name_inner{v:#field_name}, sym: #name_counter.next_sym(),_p: PhantomData, _s: PhantomData};
+                            let node = #name_node {node};
+                            T::on_new(&node);
+                            node
+                        }
+                        #vec_edit_fns
+                    }
+                    impl<T:TxSgl+ VersionCtlSgl + TxCommitSgl, #impl_params S: EgglogEnumVariantTy> Commit for #name_node<T,#node_params S>
+                    where
+                        #name_node<T, #node_params S>: EgglogNode
+                    {
+                        fn commit(&self) {
+                            T::on_commit(self);
+                        }
+                        fn stage(&self) {
+                            T::on_stage(self);
+                        }
+                    }
+                    impl<T:SingletonGetter, #impl_params> EgglogNode for #name_node<T,#node_params ()> {
+                        fn succs_mut(&mut self) -> Vec<&mut Sym>{
+                            #[allow(unused_mut)]
+                            let mut succs = vec![];
+                            self.node.ty.v.iter_mut().for_each(#succ_pushes_mut);
+                            succs
+                        }
+                        fn succs(&self) -> Vec<Sym>{
+                            #[allow(unused_mut)]
+                            let mut succs = vec![];
+                            self.node.ty.v.iter().for_each(#succ_pushes);
+                            succs
+                        }
+                        fn next_sym(&mut self) -> Sym{
+                            let next_sym = #name_counter.next_sym();
+                            self.node.sym = next_sym;
+                            next_sym.erase()
+                        }
+                        fn cur_sym(&self) -> Sym{
+                            self.node.sym.erase()
+                        }
+                        fn cur_sym_mut(&mut self) -> &mut Sym{
+                            self.node.sym.erase_mut()
+                        }
+                        fn clone_dyn(&self) -> Box<dyn EgglogNode>{
+                            Box::new(self.clone())
+                        }
+                        fn sort_name(&self) -> String{
+                            <#name_egglogty_impl #ty_generics as EgglogTy>::sort_name()
+                        }
+                        fn ctor_fields(&self) -> Option<(String, Vec<String>)>{
+                            #ctor_fields
+                        }
+                    }
+                    impl<T: SingletonGetter, #impl_params V: EgglogEnumVariantTy> EgglogTerm for #name_node<T, #node_params V> {
+                        type Sort = #name_node<T, #node_params ()>;
+                        fn term(&self) -> String {
+                            self.node.sym.to_string()
+                        }
+                    }
+                    impl<T: SingletonGetter, #impl_params V: EgglogEnumVariantTy> AsRef<#name_node<T, #node_params ()>> for #name_node<T, #node_params V> {
+                        fn as_ref(&self) -> &#name_node<T, #node_params ()> {
+                            unsafe {
+                                &*(self as *const #name_node<T,#node_params V> as *const #name_node<T,#node_params ()>)
+                            }
+                        }
+                    }
+                    impl<T:SingletonGetter, #impl_params V:EgglogEnumVariantTy > Clone for #name_node<T,#node_params V> {
+                        fn clone(&self) -> Self {
+                            Self { node: Node { ty: self.node.ty.clone(), sym: self.node.sym.clone(), _p: PhantomData, _s: PhantomData }  }
+                        }
+                    }
+                    impl<T:SingletonGetter, #impl_params V:EgglogEnumVariantTy> ToEgglog for #name_node<T,#node_params V>
+                    where #name_node<T,#node_params V>: EgglogNode{
                         fn to_egglog(&self) -> String{
-                            format!("(let {} (vec-of {}))",self.cur_sym(),self.node.ty.v.iter().fold("".to_owned(), |s,item| s+ item.as_str()+" " ))
+                            #render
                         }
                     }
-                    impl<T:TxSgl + VersionCtlSgl, V:EgglogEnumVariantTy> LocateVersion for #name_node<T,V>
-                    where #name_node<T,V> : EgglogNode
+                    impl<T:TxSgl, #node_impl_params> FromEgglog for #name_node<T,#node_params ()> {
+                        fn from_egglog(expr: &EgglogExpr) -> Self {
+                            #from_egglog
+                        }
+                    }
+                    impl<T:TxSgl + VersionCtlSgl, #impl_params V:EgglogEnumVariantTy> LocateVersion for #name_node<T,#node_params V>
+                    where #name_node<T,#node_params V> : EgglogNode
                     {
                         fn locate_latest(&mut self){
                             T::set_latest(self.cur_sym_mut());
-                            self.node.ty.v.iter_mut().for_each(|item| {T::set_latest(item.erase_mut())});
+                            self.succs_mut().into_iter().for_each(|item| T::set_latest(item));
                         }
                         fn locate_next(&mut self){
                             T::set_next(self.cur_sym_mut());
-                            self.node.ty.v.iter_mut().for_each(|item| {T::set_next(item.erase_mut())});
+                            self.succs_mut().into_iter().for_each(|item| T::set_next(item));
                         }
                         fn locate_prev(&mut self){
                             T::set_prev(self.cur_sym_mut());
-                            self.node.ty.v.iter_mut().for_each(|item| {T::set_next(item.erase_mut())});
+                            self.succs_mut().into_iter().for_each(|item| T::set_prev(item));
                         }
                     }
-                }
-            };
-            let field_ty = match first_generic.to_token_stream().to_string().as_str() {
-                x if PANIC_TY_LIST.contains(&x) => {
-                    panic!("{} not supported", x)
-                }
-                x if EGGLOG_BASIC_TY_LIST.contains(&x) => first_generic.to_token_stream(),
-                _ => {
-                    let first_generic = match &first_generic {
-                        Type::Path(type_path) => {
-                            type_path
-                                .path
-                                .segments
-                                .last()
-                                .expect("impossible")
-                                .clone()
-                                .ident
-                        }
-                        _ => panic!(
-                            "{} keep the type simple!",
-                            first_generic.to_token_stream().to_string()
-                        ),
-                    };
-                    format_ident!("{}Ty", first_generic).to_token_stream()
-                }
-            };
-            if is_vec_type(&f.ty) {
-                let vec_expanded = quote! {
-                    pub type #name_node_alias<T,V> = #egglog_wrapper_path::wrap::Node<#name_egglogty_impl,T,#name_inner,V>;
-                    #[derive(Clone,Debug)]
-                    pub struct #name_egglogty_impl;
-                    #[derive(::derive_more::DerefMut,::derive_more::Deref)]
-                    pub struct #name_node<T: SingletonGetter, V: EgglogEnumVariantTy=()> {
-                        node:#name_node_alias<T,V>
-                    }
-                    #[derive(Clone,Debug)]
-                    pub struct #name_inner {
-                        v:#egglog_wrapper_path::wrap::Syms<#field_ty>
-                    }
-                    const _:() = {
-                        use #egglog_wrapper_path::wrap::*;
-                        impl NodeInner<#name_egglogty_impl> for #name_inner{}
-                        use std::marker::PhantomData;
-                        static #name_counter: TyCounter<#name_egglogty_impl> = TyCounter::new();
-                        impl<T:TxSgl> #name_node<T,()> {
-                            pub fn new(#field_name:Vec<&#field_node_ty>) -> #name_node<T,()>{
-                                let #field_name = #field_name.into_iter().map(|r| r.as_ref().sym).collect();
-                                let node = Node{ ty: #name_inner{v:#field_name}, sym: #name_counter.next_sym(),_p: PhantomData, _s: PhantomData};
-                                let node = #name_node {node};
-                                T::on_new(&node);
-                                node
-                            }
-                        }
-                        impl<T:SingletonGetter> EgglogNode for #name_node<T,()> {
-                            fn succs_mut(&mut self) -> Vec<&mut Sym>{
-                                self.node.ty.v.iter_mut().map(|s| s.erase_mut()).collect()
-                            }
-                            fn succs(&self) -> Vec<Sym>{
-                                self.node.ty.v.iter().map(|s| s.erase()).collect()
-                            }
-                            fn next_sym(&mut self) -> Sym{
-                                let next_sym = #name_counter.next_sym();
-                                self.node.sym = next_sym;
-                                next_sym.erase()
-                            }
-                            fn cur_sym(&self) -> Sym{
-                                self.node.sym.erase()
-                            }
-                            fn cur_sym_mut(&mut self) -> &mut Sym{
-                                self.node.sym.erase_mut()
-                            }
-                            fn clone_dyn(&self) -> Box<dyn EgglogNode>{
-                                Box::new(self.clone())
-                            }
-                        }
-                        impl<T: SingletonGetter, V: EgglogEnumVariantTy> AsRef<#name_node<T, ()>> for #name_node<T, V> {
-                            fn as_ref(&self) -> &#name_node<T, ()> {
-                                unsafe {
-                                    &*(self as *const #name_node<T,V> as *const #name_node<T,()>)
-                                }
-                            }
-                        }
-                        impl<T:SingletonGetter,V:EgglogEnumVariantTy > Clone for #name_node<T,V> {
-                            fn clone(&self) -> Self {
-                                Self { node: Node { ty: self.node.ty.clone(), sym: self.node.sym.clone(), _p: PhantomData, _s: PhantomData }  }
-                            }
-                        }
-                        #to_egglog_impl
-                    };
                 };
-                vec_expanded
-            } else {
-                panic!("only support Vec for struct")
-            }
+            };
+            container_expanded
         }
         // transform   enum A{ A1{a:i32} , A2 {b:B}}
         // into struct enum ANode { A1{a:I32Sym} A2{b:BSym} }
@@ -387,7 +921,6 @@ pub fn egglog_ty(_attr: TokenStream, item: TokenStream) -> TokenStream {
             let name_node = format_ident!("{}", name);
             let _name_node = format_ident!("_{}", name);
             let name_inner = format_ident!("{}Inner", name);
-            let name_counter = format_ident!("{}_COUNTER", name.to_string().to_uppercase());
             // let name_snakecase = format_ident!("{}",name.to_string().to_snake_case());
             // let derive_more_path  = derive_more_path();
 
@@ -395,7 +928,7 @@ pub fn egglog_ty(_attr: TokenStream, item: TokenStream) -> TokenStream {
                 .variants
                 .iter()
                 .map(|variant| {
-                    let types_and_idents = variants_to_sym_typed_ident_list(variant);
+                    let types_and_idents = variants_to_sym_typed_ident_list(variant, &params);
                     let variant_name = &variant.ident;
                     quote! {#variant_name {#( #types_and_idents ),*  }}
                 })
@@ -403,17 +936,24 @@ pub fn egglog_ty(_attr: TokenStream, item: TokenStream) -> TokenStream {
 
             let to_egglog_match_arms = data_enum.variants.iter().map(|variant| {
                 let variant_idents = variant_to_field_ident(variant).collect::<Vec<_>>();
+                // literals are rendered by EgglogPrim, nodes by their syms
+                let rendered_fields = variant_to_field_kinds(variant, &params)
+                    .into_iter()
+                    .map(|(x, kind)| field_render(&x, &kind));
                 let variant_name = &variant.ident;
-                let s = " {:.3}".repeat(variant_idents.len());
-                let format_str = format!("(let {{}} ({} {}))", variant_name, s);
+                // constructors of instances of generic type are mangled, such as `ConsCtl`
+                let ctor = ctor_name(variant, &prefix, &params);
+                let s = " {}".repeat(variant_idents.len());
+                let format_str = format!("(let {{}} ({{}}{}))", s);
                 quote! {#name_inner::#variant_name {#( #variant_idents ),*  } => {
-                    format!(#format_str ,self.node.sym, #(#variant_idents),*)
+                    format!(#format_str ,self.node.sym, #ctor, #(#rendered_fields),*)
                 }}
             });
             let locate_latest_match_arms = data_enum.variants.iter().map(|variant| {
                 let variant_idents = variant_to_field_ident(variant);
                 let mapped_variant_idents = variant_to_mapped_ident_list(
                     variant,
+                    &params,
                     |_| {
                         quote! {}
                     },
@@ -434,6 +974,7 @@ pub fn egglog_ty(_attr: TokenStream, item: TokenStream) -> TokenStream {
                 let variant_idents = variant_to_field_ident(variant);
                 let mapped_variant_idents = variant_to_mapped_ident_list(
                     variant,
+                    &params,
                     |_| {
                         quote! {}
                     },
@@ -453,6 +994,7 @@ pub fn egglog_ty(_attr: TokenStream, item: TokenStream) -> TokenStream {
                 let variant_idents = variant_to_field_ident(variant);
                 let mapped_variant_idents = variant_to_mapped_ident_list(
                     variant,
+                    &params,
                     |_| {
                         quote! {}
                     },
@@ -469,43 +1011,68 @@ pub fn egglog_ty(_attr: TokenStream, item: TokenStream) -> TokenStream {
                 }
             });
             let fns = data_enum.variants.iter().map(|variant|{
-                let ref_node_list = variant_to_ref_node_list(&variant,&name);
-                let field_idents = variants_to_assign_node_field_list(&variant);
+                let ref_node_list = variant_to_ref_node_list(variant, &params);
+                let field_idents = variants_to_assign_node_field_list(variant, &params);
                 let variant_name = &variant.ident;
                 let new_fn_name = format_ident!("new_{}",variant_name.to_string().to_snake_case());
+                let variant_marker = variant_marker(name, variant);
 
                 quote! {
-                    pub fn #new_fn_name(#(#ref_node_list),*) -> #name_node<T,#variant_name>{
+                    pub fn #new_fn_name(#(#ref_node_list),*) -> #name_node<T,#node_params #variant_marker>{
                         let ty = #name_inner::#variant_name {#(#field_idents),*  };
-                        let node = Node { ty, sym: #name_counter.next_sym(), _p:PhantomData, _s:PhantomData::<#variant_name>};
+                        let node = Node { ty, sym: #name_counter.next_sym(), _p:PhantomData, _s:PhantomData::<#variant_marker>};
                         let node = #name_node {node};
                         T::on_new(&node);
                         node
                     }
                 }
             });
+            let from_egglog_arms = data_enum.variants.iter().map(|variant| {
+                let variant_name = &variant.ident;
+                let ctor = ctor_name(variant, &prefix, &params);
+                let new_fn_name = format_ident!("new_{}", variant_name.to_string().to_snake_case());
+                let kinds = variant_to_field_kinds(variant, &params);
+                let arg_count = kinds.len();
+                let args = kinds
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, (_, kind))| field_from_egglog(idx, kind));
+                quote! {
+                    if head == #ctor {
+                        assert_eq!(args.len(), #arg_count, "wrong number of args of {}", head);
+                        return Self::#new_fn_name(#(#args),*).as_ref().clone();
+                    }
+                }
+            });
             let enum_variant_tys_def = data_enum.variants.iter().map(|variant| {
                 let variant_name = &variant.ident;
+                let variant_marker = variant_marker(name, variant);
 
                 quote! {
                     #[derive(Clone)]
-                    pub struct #variant_name;
-                    impl EgglogEnumVariantTy for #variant_name {
+                    pub struct #variant_marker;
+                    impl EgglogEnumVariantTy for #variant_marker {
                         const TY_NAME:&'static str = stringify!(#variant_name);
                     }
                 }
             });
 
             let set_fns = data_enum.variants.iter().map(|variant|{
-                let ref_node_list = variant_to_ref_node_list(&variant,&name);
-                let assign_node_field_list = variants_to_assign_node_field_list_without_prefixed_ident(&variant);
+                let ref_node_list = variant_to_ref_node_list(variant, &params);
+                let assign_node_field_list = variants_to_assign_node_field_list_without_prefixed_ident(variant, &params);
                 let field_idents = variant_to_field_ident(variant).collect::<Vec<_>>();
                 let variant_name = &variant.ident;
+                let variant_marker = variant_marker(name, variant);
+                let ctor = ctor_name(variant, &prefix, &params);
+                let rendered_fields = variant_to_field_kinds(variant, &params)
+                    .into_iter()
+                    .map(|(x, kind)| field_render(&x, &kind))
+                    .collect::<Vec<_>>();
 
                 let set_fns = assign_node_field_list.iter().zip(ref_node_list.iter().zip(field_idents.iter()
                     )).map(
                     |(assign_node_field,(ref_node,field_ident))|{
-                        let set_fn_name = format_ident!("set_{}",field_ident);
+                        let set_fn_name = set_fn_name(field_ident);
                         quote! {
                             /// set fn of node, firstly update the sym version and specified field and then informs rx what happen on this node
                             /// rx's behavior depends on whether version control is enabled
@@ -520,7 +1087,7 @@ pub fn egglog_ty(_attr: TokenStream, item: TokenStream) -> TokenStream {
                         }
                     }
                 );
-                let sym_list = variants_to_sym_type_list(variant);
+                let sym_list = variants_to_sym_type_list(variant, &params);
                 let get_sym_fns = sym_list.iter().zip(field_idents.iter()
                     ).map(
                     |(sym,field_ident)|{
@@ -553,12 +1120,17 @@ pub fn egglog_ty(_attr: TokenStream, item: TokenStream) -> TokenStream {
                 );
 
                 let vec_needed_syms:Vec<_> =
-                    variant_to_field_list_without_prefixed_ident_filter_out_basic_ty(variant)
+                    variant_to_field_list_without_prefixed_ident_filter_out_basic_ty(variant, &params)
+                    .into_iter()
+                    .map(|x| format_ident!("{}",x.to_string())).collect();
+                // optional nodes are succs only when they are set
+                let option_needed_syms:Vec<_> =
+                    variant_to_option_node_field_list(variant, &params)
                     .into_iter()
                     .map(|x| format_ident!("{}",x.to_string())).collect();
 
                 quote! {
-                    impl<T:TxSgl> #name_node<T,#variant_name>{
+                    impl<T:TxSgl, #node_impl_params> #name_node<T,#node_params #variant_marker>{
                         #(
                             #set_fns
                         )*
@@ -569,17 +1141,23 @@ pub fn egglog_ty(_attr: TokenStream, item: TokenStream) -> TokenStream {
                             #get_mut_sym_fns
                         )*
                     }
-                    impl<T:SingletonGetter> EgglogNode for #name_node<T,#variant_name>{
+                    impl<T:SingletonGetter, #impl_params> EgglogNode for #name_node<T,#node_params #variant_marker>{
                         fn succs_mut(&mut self) -> Vec<&mut Sym>{
                             if let #name_inner::#variant_name{ #(#field_idents),*} = &mut self.node.ty{
-                                vec![#(#vec_needed_syms.erase_mut()),*]
+                                #[allow(unused_mut)]
+                                let mut succs = vec![#(#vec_needed_syms.erase_mut()),*];
+                                #(if let Some(x) = #option_needed_syms { succs.push(x.erase_mut()) })*
+                                succs
                             }else{
                                 panic!()
                             }
                         }
                         fn succs(&self) -> Vec<Sym>{
                             if let #name_inner::#variant_name{ #(#field_idents),*} = &self.node.ty{
-                                vec![#((#vec_needed_syms).erase()),*]
+                                #[allow(unused_mut)]
+                                let mut succs = vec![#((#vec_needed_syms).erase()),*];
+                                #(if let Some(x) = #option_needed_syms { succs.push(x.erase()) })*
+                                succs
                             }else{
                                 panic!()
                             }
@@ -598,33 +1176,41 @@ pub fn egglog_ty(_attr: TokenStream, item: TokenStream) -> TokenStream {
                         fn clone_dyn(&self) -> Box<dyn EgglogNode>{
                             Box::new(self.clone())
                         }
+                        fn sort_name(&self) -> String{
+                            <#name_egglogty_impl #ty_generics as EgglogTy>::sort_name()
+                        }
+                        fn ctor_fields(&self) -> Option<(String, Vec<String>)>{
+                            if let #name_inner::#variant_name{ #(#field_idents),*} = &self.node.ty{
+                                Some((format!("{}", #ctor), vec![#(format!("{}", #rendered_fields)),*]))
+                            }else{
+                                panic!()
+                            }
+                        }
                     }
 
                 }
             });
 
             let expanded = quote! {
-                pub type #name_node_alias<T,V> = #egglog_wrapper_path::wrap::Node<#name_egglogty_impl,T,#name_inner,V>;
+                pub type #name_node_alias<T,#node_params V> = #egglog_wrapper_path::wrap::Node<#name_egglogty_impl #ty_generics,T,#name_inner #ty_generics,V>;
                 #[derive(derive_more::Deref,)]
-                pub struct #name_node<T: SingletonGetter,V:EgglogEnumVariantTy=()> {
-                    node:#name_node_alias<T,V>
+                pub struct #name_node<T: SingletonGetter,#impl_params V:EgglogEnumVariantTy=()> {
+                    node:#name_node_alias<T,#node_params V>
                 }
-                #[derive(Debug,Clone)]
-                pub struct #name_egglogty_impl;
                 // impl NonUnitEgglogEnumVariantTy for #name_egglogty_impl { }
                 #[allow(unused)]
                 #[derive(Debug,Clone)]
-                pub enum #name_inner {
+                pub enum #name_inner #ty_generics {
                     #(#variants_def_of_node_with_syms),*
                 }
                 const _:() = {
                     use std::marker::PhantomData;
                     use #egglog_wrapper_path::*;
                     #(#enum_variant_tys_def)*
-                    impl<T:TxSgl> #name_node<T,()> {
+                    impl<T:TxSgl, #node_impl_params> #name_node<T,#node_params ()> {
                         #(#fns)*
                     }
-                    impl<T:SingletonGetter> EgglogNode for #name_node<T,()> {
+                    impl<T:SingletonGetter, #impl_params> EgglogNode for #name_node<T,#node_params ()> {
                         fn succs_mut(&mut self) -> Vec<&mut Sym>{
                             vec![]
                         }
@@ -645,17 +1231,20 @@ pub fn egglog_ty(_attr: TokenStream, item: TokenStream) -> TokenStream {
                         fn clone_dyn(&self) -> Box<dyn EgglogNode>{
                             Box::new(self.clone())
                         }
+                        fn sort_name(&self) -> String{
+                            <#name_egglogty_impl #ty_generics as EgglogTy>::sort_name()
+                        }
                     }
-                    impl<T:SingletonGetter, V:EgglogEnumVariantTy> ToEgglog for #name_node<T,V>
-                    where #name_node<T,V>: EgglogNode{
+                    impl<T:SingletonGetter, #impl_params V:EgglogEnumVariantTy> ToEgglog for #name_node<T,#node_params V>
+                    where #name_node<T,#node_params V>: EgglogNode{
                         fn to_egglog(&self) -> String{
                             match &self.node.ty{
                                 #(#to_egglog_match_arms),*
                             }
                         }
                     }
-                    impl<T:TxSgl + VersionCtlSgl, V:EgglogEnumVariantTy> LocateVersion for #name_node<T,V>
-                    where #name_node<T,V> : EgglogNode {
+                    impl<T:TxSgl + VersionCtlSgl, #impl_params V:EgglogEnumVariantTy> LocateVersion for #name_node<T,#node_params V>
+                    where #name_node<T,#node_params V> : EgglogNode {
                         fn locate_latest(&mut self) {
                             match &mut self.node.ty{
                                 #(#locate_latest_match_arms),*
@@ -672,23 +1261,29 @@ pub fn egglog_ty(_attr: TokenStream, item: TokenStream) -> TokenStream {
                             }
                         }
                     }
-                    impl<T: SingletonGetter,  V: EgglogEnumVariantTy> AsRef<#name_node<T, ()>> for #name_node<T, V> {
-                        fn as_ref(&self) -> &#name_node<T, ()> {
+                    impl<T: SingletonGetter, #impl_params V: EgglogEnumVariantTy> EgglogTerm for #name_node<T, #node_params V> {
+                        type Sort = #name_node<T, #node_params ()>;
+                        fn term(&self) -> String {
+                            self.node.sym.to_string()
+                        }
+                    }
+                    impl<T: SingletonGetter, #impl_params V: EgglogEnumVariantTy> AsRef<#name_node<T, #node_params ()>> for #name_node<T, #node_params V> {
+                        fn as_ref(&self) -> &#name_node<T, #node_params ()> {
                             unsafe {
-                                &*(self as *const #name_node<T,V> as *const #name_node<T,()>)
+                                &*(self as *const #name_node<T,#node_params V> as *const #name_node<T,#node_params ()>)
                             }
                         }
                     }
 
-                    impl<T:SingletonGetter,V:EgglogEnumVariantTy > Clone for #name_node<T,V> {
+                    impl<T:SingletonGetter, #impl_params V:EgglogEnumVariantTy > Clone for #name_node<T,#node_params V> {
                         fn clone(&self) -> Self {
                             Self { node: Node { ty: self.ty.clone(), sym: self.sym.clone(), _p: PhantomData, _s: PhantomData }  }
                         }
                     }
 
-                    impl<T:TxSgl+ VersionCtlSgl + TxCommitSgl,S: EgglogEnumVariantTy> Commit for #name_node<T,S>
+                    impl<T:TxSgl+ VersionCtlSgl + TxCommitSgl, #impl_params S: EgglogEnumVariantTy> Commit for #name_node<T,#node_params S>
                     where
-                        #name_node<T, S>: EgglogNode
+                        #name_node<T, #node_params S>: EgglogNode
                     {
                         fn commit(&self) {
                             T::on_commit(self);
@@ -698,14 +1293,23 @@ pub fn egglog_ty(_attr: TokenStream, item: TokenStream) -> TokenStream {
                         }
                     }
 
-                    impl NodeInner<#name_egglogty_impl> for #name_inner {}
-                    static #name_counter: TyCounter<#name_egglogty_impl> = TyCounter::new();
+                    impl<T:TxSgl, #node_impl_params> FromEgglog for #name_node<T,#node_params ()> {
+                        /// create nodes of the term by `new_*` fns, so they are also received by T
+                        fn from_egglog(expr: &EgglogExpr) -> Self {
+                            let (head, args) = expr_to_call(expr);
+                            #(#from_egglog_arms)*
+                            panic!("unknown constructor {} of {}", head, stringify!(#name_node))
+                        }
+                    }
+
+                    impl<#impl_params> NodeInner<#name_egglogty_impl #ty_generics> for #name_inner #ty_generics {}
+                    #counter_def
                     #(#set_fns)*
                 };
             };
             expanded
         }
-        Data::Union(_) => todo!(),
+        Data::Union(_) => unreachable!("union is rejected by check_egglog_ty"),
     };
 
     TokenStream::from(quote! {
@@ -0,0 +1,7 @@
+/// unsupported input of macros is reported at the offending item, messages are locked by `.stderr`
+/// files next to each case. run with `TRYBUILD=overwrite` to accept changed messages
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
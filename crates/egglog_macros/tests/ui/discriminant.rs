@@ -0,0 +1,9 @@
+use egglog_macros::egglog_ty;
+
+#[egglog_ty]
+enum Level {
+    Low = 1,
+    High = 2,
+}
+
+fn main() {}
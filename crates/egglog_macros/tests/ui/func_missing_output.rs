@@ -0,0 +1,6 @@
+use egglog_macros::egglog_func;
+
+#[egglog_func]
+struct Current {}
+
+fn main() {}
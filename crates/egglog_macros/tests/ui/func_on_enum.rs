@@ -0,0 +1,8 @@
+use egglog_macros::egglog_func;
+
+#[egglog_func(output = Level)]
+enum Current {
+    Low,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use egglog_macros::egglog_func;
+
+struct Ctl;
+struct List<E>(E);
+
+#[egglog_func(output=Ctl)]
+struct Last {
+    list: List<Ctl>,
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use egglog_macros::egglog_ty;
+
+#[egglog_ty]
+struct Shelf {
+    len: i64,
+    cur: String,
+    width: i64,
+}
+
+#[egglog_ty]
+enum Cursor {
+    At { next: i64, commit: i64 },
+}
+
+fn main() {}
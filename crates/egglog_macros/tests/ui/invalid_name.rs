@@ -0,0 +1,8 @@
+use egglog_macros::egglog_ty;
+
+#[egglog_ty(name = "geo Point")]
+enum Point {
+    Origin {},
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use egglog_macros::egglog_ty;
+
+#[egglog_ty]
+struct Env {
+    v: std::collections::BTreeMap<String>,
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use egglog_macros::egglog_ty;
+
+#[egglog_ty]
+enum Cons {
+    Value { v: i64, con: Box<Cosn> },
+    End {},
+}
+
+#[egglog_ty]
+struct Sprite {
+    name: String,
+    shadow: Option<Colour>,
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use egglog_macros::egglog_ty;
+
+#[egglog_ty]
+enum Config {
+    Value { value: Option<Option<i64>> },
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use egglog_macros::egglog_ty;
+
+#[egglog_ty]
+struct Slots {
+    v: Vec<Option<i64>>,
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use egglog_macros::egglog_ty;
+
+#[egglog_ty(sort)]
+enum Level {
+    Low,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use egglog_macros::egglog_ty;
+
+#[egglog_ty]
+union Number {
+    int: i64,
+    float: f64,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use egglog_macros::egglog_ty;
+
+#[egglog_ty]
+enum Point {
+    Pair { xy: (f64, f64) },
+    Name { name: &'static str },
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use egglog_macros::egglog_ty;
+
+#[egglog_ty]
+enum Tree<T> {
+    Leaf { value: i64 },
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use egglog_macros::egglog_ty;
+
+#[egglog_ty]
+enum Level {
+    #[egglog(rename = "Lo")]
+    Low {},
+    #[egglog(name = "(High)")]
+    High {},
+    #[egglog(cost = -1)]
+    Mid {},
+}
+
+fn main() {}
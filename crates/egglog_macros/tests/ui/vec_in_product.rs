@@ -0,0 +1,9 @@
+use egglog_macros::egglog_ty;
+
+#[egglog_ty]
+struct Polygon {
+    name: String,
+    points: Vec<i64>,
+}
+
+fn main() {}
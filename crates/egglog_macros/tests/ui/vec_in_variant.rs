@@ -0,0 +1,8 @@
+use egglog_macros::egglog_ty;
+
+#[egglog_ty]
+enum Shape {
+    Polygon { points: Vec<i64> },
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use egglog_macros::egglog_ty;
+
+#[egglog_ty]
+enum Id {
+    Seq { seq: u64 },
+    Index { index: Option<usize> },
+}
+
+fn main() {}
@@ -0,0 +1,28 @@
+use egglog_wrapper::tx_remote::serve;
+use std::net::TcpListener;
+
+const USAGE: &str = "usage: egraph_server tcp <address> | egraph_server unix <path>";
+
+/// standalone egraph server which TxRemote connects to
+fn main() {
+    let args = std::env::args().collect::<Vec<_>>();
+    match (args.get(1).map(String::as_str), args.get(2)) {
+        (Some("tcp"), Some(addr)) => {
+            let listener = TcpListener::bind(addr)
+                .unwrap_or_else(|e| panic!("failed to bind {addr}: {e}"));
+            println!("egraph server listening on {}", listener.local_addr().unwrap());
+            serve(listener.incoming());
+        }
+        #[cfg(unix)]
+        (Some("unix"), Some(path)) => {
+            let listener = std::os::unix::net::UnixListener::bind(path)
+                .unwrap_or_else(|e| panic!("failed to bind {path}: {e}"));
+            println!("egraph server listening on {path}");
+            serve(listener.incoming());
+        }
+        _ => {
+            eprintln!("{USAGE}");
+            std::process::exit(2);
+        }
+    }
+}
@@ -0,0 +1,90 @@
+//! facts checked against egraph, used as assertions on the results of rules
+//!
+//! facts are built from typed terms by [`Fact::eq`] and [`Fact::holds`] or the [`check!`](crate::check!)
+//! macro, a failed check is explained by the terms both sides are extracted to
+use crate::{query, wrap::EgglogTerm};
+use egglog::EGraph;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fact {
+    /// both terms are in the same eclass, `(= lhs rhs)`
+    Eq(String, String),
+    /// term has a value, such as a function call with a row
+    Holds(String),
+}
+
+impl Fact {
+    pub fn eq<S, L: EgglogTerm<Sort = S>, R: EgglogTerm<Sort = S>>(lhs: &L, rhs: &R) -> Fact {
+        Fact::Eq(lhs.term(), rhs.term())
+    }
+    pub fn holds(term: &impl EgglogTerm) -> Fact {
+        Fact::Holds(term.term())
+    }
+}
+
+impl fmt::Display for Fact {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Fact::Eq(lhs, rhs) => write!(f, "(= {lhs} {rhs})"),
+            Fact::Holds(term) => write!(f, "{term}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CheckFailure {
+    pub fact: Fact,
+    /// why the fact doesn't hold, such as the terms both sides are extracted to
+    pub reason: String,
+}
+
+impl fmt::Display for CheckFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "check {} failed: {}", self.fact, self.reason)
+    }
+}
+
+impl std::error::Error for CheckFailure {}
+
+/// check fact by egglog `check` command, terms should be committed
+pub fn check(egraph: &mut EGraph, fact: &Fact) -> Result<(), CheckFailure> {
+    let Err(e) = egraph.parse_and_run_program(None, &format!("(check {fact})")) else {
+        return Ok(());
+    };
+    let extracted = |egraph: &mut EGraph, term: &str| {
+        query::extract(egraph, term).map_err(|e| format!("{term} has no value: {e}"))
+    };
+    let reason = match fact {
+        Fact::Eq(lhs, rhs) => match (extracted(egraph, lhs), extracted(egraph, rhs)) {
+            (Ok(lhs_term), Ok(rhs_term)) => format!(
+                "{lhs} is {lhs_term} but {rhs} is {rhs_term}, they are not proven equal"
+            ),
+            (Err(reason), _) | (_, Err(reason)) => reason,
+        },
+        Fact::Holds(term) => extracted(egraph, term).err().unwrap_or_else(|| e.to_string()),
+    };
+    Err(CheckFailure {
+        fact: fact.clone(),
+        reason,
+    })
+}
+
+/// build [`Fact`] from `lhs == rhs` or a single term, such as `check!(Selected::apply(()) == root)`
+///
+/// both sides are borrowed, so nodes are passed by value
+#[macro_export]
+macro_rules! check {
+    (@lhs [$($lhs:tt)+] == $($rhs:tt)+) => {
+        $crate::check::Fact::eq(&($($lhs)+), &($($rhs)+))
+    };
+    (@lhs [$($lhs:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::check!(@lhs [$($lhs)* $next] $($rest)*)
+    };
+    (@lhs [$($lhs:tt)+]) => {
+        $crate::check::Fact::holds(&($($lhs)+))
+    };
+    ($($fact:tt)+) => {
+        $crate::check!(@lhs [] $($fact)+)
+    };
+}
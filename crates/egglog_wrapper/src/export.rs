@@ -0,0 +1,101 @@
+//! export egraph and work area to files for inspectors
+//!
+//! egraph is exported by egraph-serialize as graphviz dot or its JSON format. work area is exported
+//! as JSON of its nodes, so that the version DAG on the rust side could be visualised along with it:
+//! ```text
+//! {"nodes":[{"sym":"cons2","sort":"Cons","ctor":"Value","fields":["2","cons1"],"term":"(Value 2 cons1)",
+//!   "preds":["root0"],"succs":["cons1"],"next":"cons3","prev":null}]}
+//! ```
+use crate::wrap::{Sym, WorkAreaNode};
+use egglog::{EGraph, SerializeConfig};
+use std::{fmt::Write, ops::Deref, path::PathBuf};
+
+/// write egraph as graphviz dot to file_name with `dot` extension
+pub fn egraph_to_dot(egraph: &EGraph, file_name: PathBuf) {
+    let serialized = egraph.serialize(SerializeConfig::default());
+    let dot_path = file_name.with_extension("dot");
+    serialized
+        .to_dot_file(dot_path.clone())
+        .unwrap_or_else(|_| panic!("Failed to write dot file to {dot_path:?}"));
+}
+
+/// write egraph in JSON format of egraph-serialize to file_name with `json` extension
+pub fn egraph_to_json(egraph: &EGraph, file_name: PathBuf) {
+    let serialized = egraph.serialize(SerializeConfig::default());
+    let json_path = file_name.with_extension("json");
+    serialized
+        .to_json_file(json_path.clone())
+        .unwrap_or_else(|_| panic!("Failed to write json file to {json_path:?}"));
+}
+
+/// write JSON of work area nodes to file_name with `json` extension
+pub fn work_area_to_json(
+    nodes: impl IntoIterator<Item = impl Deref<Target = WorkAreaNode>>,
+    file_name: PathBuf,
+) {
+    let json_path = file_name.with_extension("json");
+    std::fs::write(&json_path, work_area_json(nodes))
+        .unwrap_or_else(|_| panic!("Failed to write json file to {json_path:?}"));
+}
+
+/// JSON of work area nodes, ordered by sym
+///
+/// ctor and fields are told by the node, fields are literals or syms of succs. they are null for
+/// nodes which don't tell them, and term is their `to_egglog` then
+pub fn work_area_json(nodes: impl IntoIterator<Item = impl Deref<Target = WorkAreaNode>>) -> String {
+    let mut nodes = nodes
+        .into_iter()
+        .map(|node| (node.cur_sym().to_string(), node_json(&node)))
+        .collect::<Vec<_>>();
+    nodes.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let nodes = nodes.into_iter().map(|(_, json)| json).collect::<Vec<_>>();
+    format!("{{\"nodes\":[{}]}}", nodes.join(","))
+}
+
+fn node_json(node: &WorkAreaNode) -> String {
+    let sym = node.cur_sym();
+    let (ctor, fields, term) = match node.ctor_fields() {
+        Some((ctor, fields)) => {
+            let term = fields.iter().fold(format!("({ctor}"), |term, field| term + " " + field) + ")";
+            (json_str(&ctor), json_strs(fields.into_iter()), term)
+        }
+        None => ("null".to_owned(), "null".to_owned(), node.to_egglog()),
+    };
+    let opt = |sym: Option<Sym>| sym.map_or("null".to_owned(), |sym| json_str(sym.as_str()));
+    format!(
+        "{{\"sym\":{},\"sort\":{},\"ctor\":{},\"fields\":{},\"term\":{},\"preds\":{},\"succs\":{},\"next\":{},\"prev\":{}}}",
+        json_str(sym.as_str()),
+        json_str(&node.sort_name()),
+        ctor,
+        fields,
+        json_str(&term),
+        json_strs(node.preds.iter().map(|pred| pred.to_string())),
+        json_strs(node.egglog.succs().iter().map(|succ| succ.to_string())),
+        opt(node.next),
+        opt(node.prev),
+    )
+}
+
+fn json_strs(items: impl Iterator<Item = String>) -> String {
+    format!("[{}]", items.map(|item| json_str(&item)).collect::<Vec<_>>().join(","))
+}
+
+fn json_str(s: &str) -> String {
+    let mut json = String::with_capacity(s.len() + 2);
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
@@ -0,0 +1,347 @@
+//! extract terms from serialized egraph by cost models written in rust
+//!
+//! egglog extracts by `:cost` of constructors only, here the egraph is serialized into
+//! [`SerializedEGraph`] and an [`Extractor`] chooses an enode for each eclass by a [`CostModel`],
+//! so that costs could depend on literals or sizes of containers
+use crate::{query, wrap::EgglogTy};
+use egglog::{EGraph, SerializeConfig};
+use std::{
+    collections::{BTreeMap, HashMap},
+    str::FromStr,
+};
+
+pub type Cost = f64;
+
+/// sorts whose values are rendered as bare literals rather than calls
+const LITERAL_SORTS: [&str; 7] = ["i64", "f64", "String", "bool", "Unit", "BigInt", "BigRat"];
+
+/// enode of serialized egraph, children are eclasses
+#[derive(Debug, Clone)]
+pub struct ENode {
+    pub op: String,
+    pub eclass: usize,
+    pub children: Vec<usize>,
+    /// cost annotated by `:cost`, 1 by default
+    pub cost: Cost,
+}
+
+#[derive(Debug, Clone)]
+pub struct EClass {
+    pub sort: String,
+    pub nodes: Vec<usize>,
+}
+
+/// egraph serialized by egglog, eclasses and enodes are indexed by position
+#[derive(Debug, Clone, Default)]
+pub struct SerializedEGraph {
+    pub nodes: Vec<ENode>,
+    pub classes: Vec<EClass>,
+    pub roots: Vec<usize>,
+}
+
+impl SerializedEGraph {
+    /// serialize egraph with eclass of egglog expression such as a node sym as the only root
+    pub fn serialize(egraph: &mut EGraph, root: &str) -> Result<Self, String> {
+        let root = query::parse_term(root);
+        let (sort, value) = egraph.eval_expr(&root).map_err(|e| e.to_string())?;
+        let serialized = egraph.serialize(SerializeConfig {
+            root_eclasses: vec![(sort, value)],
+            ..SerializeConfig::default()
+        });
+        let mut class_idx = HashMap::new();
+        let mut classes = vec![];
+        let mut class_of = |class_id| {
+            *class_idx.entry(class_id).or_insert_with_key(|class_id| {
+                let sort = serialized
+                    .class_data
+                    .get(class_id)
+                    .and_then(|data| data.typ.clone())
+                    .unwrap_or_default();
+                classes.push(EClass { sort, nodes: vec![] });
+                classes.len() - 1
+            })
+        };
+        let mut nodes = Vec::with_capacity(serialized.nodes.len());
+        for (_, node) in &serialized.nodes {
+            let children = node
+                .children
+                .iter()
+                .map(|child| class_of(serialized.nodes[child].eclass.clone()))
+                .collect();
+            nodes.push(ENode {
+                op: node.op.clone(),
+                eclass: class_of(node.eclass.clone()),
+                children,
+                cost: node.cost.into_inner(),
+            });
+        }
+        let roots = serialized
+            .root_eclasses
+            .iter()
+            .map(|class_id| class_of(class_id.clone()))
+            .collect();
+        for (idx, node) in nodes.iter().enumerate() {
+            classes[node.eclass].nodes.push(idx);
+        }
+        Ok(SerializedEGraph { nodes, classes, roots })
+    }
+    pub fn node(&self, idx: usize) -> ENodeRef<'_> {
+        ENodeRef { egraph: self, idx }
+    }
+    /// render term rooted at eclass by chosen enodes, None if the class is not chosen or choices are cyclic
+    pub fn term(&self, choices: &Choices, class: usize) -> Option<String> {
+        fn render(
+            egraph: &SerializedEGraph,
+            choices: &Choices,
+            class: usize,
+            visiting: &mut Vec<usize>,
+        ) -> Option<String> {
+            if visiting.contains(&class) {
+                return None;
+            }
+            let node = &egraph.nodes[*choices.get(&class)?];
+            if node.children.is_empty() && LITERAL_SORTS.contains(&egraph.classes[class].sort.as_str()) {
+                return Some(node.op.clone());
+            }
+            visiting.push(class);
+            let mut term = format!("({}", node.op);
+            for child in &node.children {
+                term.push(' ');
+                term.push_str(&render(egraph, choices, *child, visiting)?);
+            }
+            visiting.pop();
+            term.push(')');
+            Some(term)
+        }
+        render(self, choices, class, &mut vec![])
+    }
+}
+
+/// view of enode handed to cost models
+#[derive(Clone, Copy)]
+pub struct ENodeRef<'a> {
+    egraph: &'a SerializedEGraph,
+    idx: usize,
+}
+
+impl<'a> ENodeRef<'a> {
+    /// constructor, or literal such as `3` and `"x"`
+    pub fn op(&self) -> &'a str {
+        &self.egraph.nodes[self.idx].op
+    }
+    pub fn sort(&self) -> &'a str {
+        &self.egraph.classes[self.egraph.nodes[self.idx].eclass].sort
+    }
+    /// cost annotated by `:cost`
+    pub fn annotated_cost(&self) -> Cost {
+        self.egraph.nodes[self.idx].cost
+    }
+    pub fn arity(&self) -> usize {
+        self.egraph.nodes[self.idx].children.len()
+    }
+    /// literal arg at idx, such as seconds of `(DurationBySecs 1.5)`
+    ///
+    /// literals are values so their eclass holds a single enode, strings keep their quotes
+    pub fn lit<L: FromStr>(&self, idx: usize) -> Option<L> {
+        let class = &self.egraph.classes[*self.egraph.nodes[self.idx].children.get(idx)?];
+        match class.nodes.as_slice() {
+            [node] => self.egraph.nodes[*node].op.parse().ok(),
+            _ => None,
+        }
+    }
+    /// number of elements of container arg at idx, such as segments of `(vec-of a b c)`
+    pub fn container_len(&self, idx: usize) -> Option<usize> {
+        let class = &self.egraph.classes[*self.egraph.nodes[self.idx].children.get(idx)?];
+        class
+            .nodes
+            .iter()
+            .map(|node| self.egraph.nodes[*node].children.len())
+            .max()
+    }
+}
+
+/// cost of enode itself, extractors add up costs of its children
+///
+/// costs should be non-negative, extractors panic on negative or NaN costs as relaxing
+/// a cycle of negative costs never ends
+pub trait CostModel {
+    fn node_cost(&self, node: ENodeRef) -> Cost;
+}
+
+/// costs annotated by `#[egglog(cost = ..)]`, the same as egglog extraction
+pub struct AnnotatedCost;
+impl CostModel for AnnotatedCost {
+    fn node_cost(&self, node: ENodeRef) -> Cost {
+        node.annotated_cost()
+    }
+}
+
+type CostFn = Box<dyn Fn(ENodeRef) -> Cost + Send + Sync>;
+
+/// cost functions per generated type, enodes of other sorts fall back to annotated costs
+#[derive(Default)]
+pub struct TyCosts {
+    fns: HashMap<String, CostFn>,
+}
+impl TyCosts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// cost enodes of T by f, such as `TyCosts::new().on::<DurationTy>(|n| n.lit(0).unwrap_or(1.0))`
+    pub fn on<T: EgglogTy>(mut self, f: impl Fn(ENodeRef) -> Cost + Send + Sync + 'static) -> Self {
+        self.fns.insert(T::sort_name(), Box::new(f));
+        self
+    }
+}
+impl CostModel for TyCosts {
+    fn node_cost(&self, node: ENodeRef) -> Cost {
+        match self.fns.get(node.sort()) {
+            Some(f) => f(node),
+            None => node.annotated_cost(),
+        }
+    }
+}
+
+/// chosen enode of each eclass
+pub type Choices = HashMap<usize, usize>;
+
+/// choose an enode for every eclass with finite cost
+pub trait Extractor {
+    fn extract(&self, egraph: &SerializedEGraph) -> Choices;
+}
+
+/// minimize cost of term as a tree, shared subterms are counted every time they occur
+pub struct TreeExtractor<C: CostModel> {
+    pub cost_model: C,
+}
+impl<C: CostModel> Extractor for TreeExtractor<C> {
+    fn extract(&self, egraph: &SerializedEGraph) -> Choices {
+        tree_costs(egraph, &node_costs(egraph, &self.cost_model)).1
+    }
+}
+
+/// best term through each enode of class ranked by tree cost, at most k distinct terms are returned
+///
+/// subterms are the cheapest ones, so variants differ in their root enode like egglog's `extract_variants`
+pub fn variants(
+    egraph: &SerializedEGraph,
+    cost_model: &impl CostModel,
+    class: usize,
+    k: usize,
+) -> Vec<(Cost, String)> {
+    let node_costs = node_costs(egraph, cost_model);
+    let (costs, choices) = tree_costs(egraph, &node_costs);
+    let mut variants = egraph.classes[class]
+        .nodes
+        .iter()
+        .filter_map(|idx| {
+            let children_cost = egraph.nodes[*idx]
+                .children
+                .iter()
+                .map(|child| costs.get(child).copied())
+                .sum::<Option<Cost>>()?;
+            let mut choices = choices.clone();
+            choices.insert(class, *idx);
+            // the variant is dropped if its subterms lead back to the class
+            let term = egraph.term(&choices, class)?;
+            Some((node_costs[*idx] + children_cost, term))
+        })
+        .collect::<Vec<_>>();
+    variants.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+    let mut distinct = vec![];
+    for (cost, term) in variants {
+        if distinct.len() == k {
+            break;
+        }
+        if distinct.iter().all(|(_, collected)| *collected != term) {
+            distinct.push((cost, term));
+        }
+    }
+    distinct
+}
+
+/// cost of cheapest tree of each eclass and its enode
+fn tree_costs(egraph: &SerializedEGraph, node_costs: &[Cost]) -> (HashMap<usize, Cost>, Choices) {
+    let mut costs = HashMap::<usize, Cost>::new();
+    let mut choices = Choices::new();
+    // relax until fixpoint, classes only reachable through cycles never get a cost
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (idx, node) in egraph.nodes.iter().enumerate() {
+            let children_cost = node
+                .children
+                .iter()
+                .map(|child| costs.get(child).copied())
+                .sum::<Option<Cost>>();
+            let Some(children_cost) = children_cost else {
+                continue;
+            };
+            let cost = node_costs[idx] + children_cost;
+            if costs.get(&node.eclass).is_none_or(|best| cost < *best) {
+                costs.insert(node.eclass, cost);
+                choices.insert(node.eclass, idx);
+                changed = true;
+            }
+        }
+    }
+    (costs, choices)
+}
+
+/// minimize cost of term as a dag greedily, shared subterms are counted once
+///
+/// each eclass keeps cost of every eclass in its chosen sub dag, so enodes reusing a subterm
+/// of a sibling don't pay for it twice
+pub struct GreedyDagExtractor<C: CostModel> {
+    pub cost_model: C,
+}
+impl<C: CostModel> Extractor for GreedyDagExtractor<C> {
+    fn extract(&self, egraph: &SerializedEGraph) -> Choices {
+        let node_costs = node_costs(egraph, &self.cost_model);
+        let mut dags = HashMap::<usize, BTreeMap<usize, Cost>>::new();
+        let mut totals = HashMap::<usize, Cost>::new();
+        let mut choices = Choices::new();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (idx, node) in egraph.nodes.iter().enumerate() {
+                if !node.children.iter().all(|child| dags.contains_key(child)) {
+                    continue;
+                }
+                let mut dag = BTreeMap::new();
+                for child in &node.children {
+                    dag.extend(dags[child].iter().map(|(class, cost)| (*class, *cost)));
+                }
+                // a node depending on its own eclass would make the dag cyclic
+                if dag.contains_key(&node.eclass) {
+                    continue;
+                }
+                dag.insert(node.eclass, node_costs[idx]);
+                let total = dag.values().sum::<Cost>();
+                if totals.get(&node.eclass).is_none_or(|best| total < *best) {
+                    totals.insert(node.eclass, total);
+                    dags.insert(node.eclass, dag);
+                    choices.insert(node.eclass, idx);
+                    changed = true;
+                }
+            }
+        }
+        choices
+    }
+}
+
+fn node_costs(egraph: &SerializedEGraph, cost_model: &impl CostModel) -> Vec<Cost> {
+    (0..egraph.nodes.len())
+        .map(|idx| {
+            let node = egraph.node(idx);
+            let cost = cost_model.node_cost(node);
+            assert!(
+                cost >= 0.0,
+                "cost of {} should be non-negative, found {}",
+                node.op(),
+                cost
+            );
+            cost
+        })
+        .collect()
+}
@@ -1,21 +1,40 @@
 pub use derive_more;
+pub mod check;
+pub mod export;
+pub mod extract;
+pub mod pattern;
+pub mod query;
 pub mod tx;
+pub mod tx_async;
+pub mod tx_mock;
+pub mod tx_record;
+pub mod tx_remote;
 pub mod tx_rx_vt;
 pub mod tx_vt;
 pub mod wrap;
 
-use crate::wrap::{FuncSort, TySort};
+use crate::wrap::{FuncSort, SortCollector, SortDefs};
 pub mod tx_minimal;
 
+/// render definitions of all types and functions, panic if their egglog names conflict
 pub fn collect_string_type_defs() -> String {
-    let mut ty_defs = "".to_owned();
-    for sort in inventory::iter::<TySort> {
-        ty_defs.push_str(sort.0);
+    // sorts are rendered now that custom names and sorts of type args are known
+    let mut sort_defs = SortDefs::default();
+    for collector in inventory::iter::<SortCollector> {
+        (collector.0)(&mut sort_defs);
     }
-    let mut func_defs = "".to_owned();
-    for sort in inventory::iter::<FuncSort> {
-        func_defs.push_str(sort.0);
+    let funcs = inventory::iter::<FuncSort>
+        .into_iter()
+        .map(|func| (func.0, func.1));
+    let conflicts = sort_defs.conflicts(funcs);
+    if !conflicts.is_empty() {
+        panic!("conflicting egglog names:\n  {}", conflicts.join("\n  "));
     }
+    let ty_defs = sort_defs.defs().collect::<String>();
+    let func_defs = inventory::iter::<FuncSort>
+        .into_iter()
+        .map(|func| (func.2)())
+        .collect::<String>();
     format!("(datatype* {} ) {}", ty_defs, func_defs)
 }
 
@@ -107,3 +126,96 @@ macro_rules! basic_tx_rx_vt {
         }
     };
 }
+
+/// macro to quickly define a Transimitter with version control whose egraph lives in a worker thread
+#[macro_export]
+macro_rules! basic_tx_async {
+    ($name:ident) => {
+        struct $name {
+            tx: egglog_wrapper::tx_async::TxAsync,
+        }
+        impl SingletonGetter for $name {
+            type RetTy = egglog_wrapper::tx_async::TxAsync;
+            fn sgl() -> &'static egglog_wrapper::tx_async::TxAsync {
+                static INSTANCE: std::sync::OnceLock<$name> = std::sync::OnceLock::new();
+                &INSTANCE
+                    .get_or_init(|| -> $name {
+                        Self {
+                            tx: egglog_wrapper::tx_async::TxAsync::new(),
+                        }
+                    })
+                    .tx
+            }
+        }
+    };
+}
+
+/// macro to quickly define a Transimitter with version control connected to egraph server at tcp address
+#[macro_export]
+macro_rules! basic_tx_remote {
+    ($name:ident, $addr:expr) => {
+        struct $name {
+            tx: egglog_wrapper::tx_remote::TxRemote,
+        }
+        impl SingletonGetter for $name {
+            type RetTy = egglog_wrapper::tx_remote::TxRemote;
+            fn sgl() -> &'static egglog_wrapper::tx_remote::TxRemote {
+                static INSTANCE: std::sync::OnceLock<$name> = std::sync::OnceLock::new();
+                &INSTANCE
+                    .get_or_init(|| -> $name {
+                        Self {
+                            tx: egglog_wrapper::tx_remote::TxRemote::connect_tcp($addr)
+                                .expect("failed to connect to egraph server"),
+                        }
+                    })
+                    .tx
+            }
+        }
+    };
+}
+
+/// macro to quickly define a Transimitter with version control which records an egglog transcript
+#[macro_export]
+macro_rules! basic_tx_record {
+    ($name:ident) => {
+        struct $name {
+            tx: egglog_wrapper::tx_record::TxRecord,
+        }
+        impl SingletonGetter for $name {
+            type RetTy = egglog_wrapper::tx_record::TxRecord;
+            fn sgl() -> &'static egglog_wrapper::tx_record::TxRecord {
+                static INSTANCE: std::sync::OnceLock<$name> = std::sync::OnceLock::new();
+                &INSTANCE
+                    .get_or_init(|| -> $name {
+                        Self {
+                            tx: egglog_wrapper::tx_record::TxRecord::new(),
+                        }
+                    })
+                    .tx
+            }
+        }
+    };
+}
+
+/// macro to quickly define a Transimitter without egraph which logs every call, for unit tests
+#[macro_export]
+macro_rules! basic_tx_mock {
+    ($name:ident) => {
+        struct $name {
+            tx: egglog_wrapper::tx_mock::TxMock,
+        }
+        impl SingletonGetter for $name {
+            type RetTy = egglog_wrapper::tx_mock::TxMock;
+            fn sgl() -> &'static egglog_wrapper::tx_mock::TxMock {
+                static INSTANCE: std::sync::OnceLock<$name> = std::sync::OnceLock::new();
+                &INSTANCE
+                    .get_or_init(|| -> $name {
+                        Self {
+                            tx: egglog_wrapper::tx_mock::TxMock::new(),
+                        }
+                    })
+                    .tx
+            }
+        }
+    };
+}
@@ -0,0 +1,105 @@
+//! query egraph by patterns and read typed bindings of their variables
+//!
+//! a [`Pattern`] is egglog query facts with declared variables, such as
+//! `Pattern::new("(= p (OffsetPoint (CurAnchorOf a) off))").var::<Point<MyTx>>("p").var::<i64>("off")`,
+//! each [`Match`] binds the variables to nodes or primitives extracted from their eclasses
+use crate::{
+    query,
+    wrap::{EgglogBinding, EgglogExpr, EgglogTerm, expr_to_call},
+};
+
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    facts: String,
+    /// declared variables and their sorts, in order of the columns of matches
+    vars: Vec<(String, String)>,
+}
+
+impl Pattern {
+    /// egglog query facts, committed nodes could be referred by their terms such as `node.term()`
+    pub fn new(facts: impl Into<String>) -> Self {
+        Pattern {
+            facts: facts.into(),
+            vars: vec![],
+        }
+    }
+    /// declare variable of facts bound to T, variables not declared are matched but not read
+    pub fn var<T: EgglogBinding>(mut self, name: &str) -> Self {
+        self.vars.push((name.to_owned(), T::sort_name()));
+        self
+    }
+    /// add fact that var is equal to term, such as `(= p root)` for a committed node root
+    pub fn eq(self, var: &str, term: &impl EgglogTerm) -> Self {
+        Pattern {
+            facts: format!("{} (= {} {})", self.facts, var, term.term()),
+            vars: self.vars,
+        }
+    }
+    pub fn facts(&self) -> &str {
+        &self.facts
+    }
+    /// run query on egraph, matches are ordered as rows of egglog
+    pub fn run(
+        &self,
+        egraph: &mut egglog::EGraph,
+        max_size: Option<usize>,
+    ) -> Result<Vec<Match>, String> {
+        let vars = self
+            .vars
+            .iter()
+            .map(|(var, sort)| (var.as_str(), sort.as_str()))
+            .collect::<Vec<_>>();
+        let rows = query::matches(egraph, &self.facts, &vars, max_size)?;
+        Ok(rows
+            .iter()
+            .map(|row| {
+                let row = query::parse_term(row);
+                let (_, args) = expr_to_call(&row);
+                Match {
+                    bindings: self
+                        .vars
+                        .iter()
+                        .zip(args)
+                        .map(|((var, sort), arg)| (var.clone(), sort.clone(), arg.clone()))
+                        .collect(),
+                }
+            })
+            .collect())
+    }
+}
+
+/// bindings of declared variables of a pattern, terms are the cheapest ones of bound eclasses
+#[derive(Debug, Clone)]
+pub struct Match {
+    bindings: Vec<(String, String, EgglogExpr)>,
+}
+
+impl Match {
+    /// value bound to var, nodes of the term are created by the Tx of T
+    ///
+    /// panic if var is not declared or it's declared with another sort
+    pub fn get<T: EgglogBinding>(&self, var: &str) -> T {
+        let (_, sort, term) = self
+            .bindings
+            .iter()
+            .find(|(name, _, _)| name == var)
+            .unwrap_or_else(|| panic!("variable {var} is not declared in pattern"));
+        assert_eq!(
+            *sort,
+            T::sort_name(),
+            "variable {var} is declared as {sort} but read as {}",
+            T::sort_name()
+        );
+        T::from_egglog(term)
+    }
+    /// term bound to var
+    pub fn term(&self, var: &str) -> Option<&EgglogExpr> {
+        self.bindings
+            .iter()
+            .find(|(name, _, _)| name == var)
+            .map(|(_, _, term)| term)
+    }
+    pub fn vars(&self) -> impl Iterator<Item = &str> {
+        self.bindings.iter().map(|(name, _, _)| name.as_str())
+    }
+}
@@ -0,0 +1,103 @@
+//! read terms out of egraph
+//!
+//! terms are returned in their egglog string repr so that they could be sent to a remote Tx as is,
+//! use [`parse_term`] and [`FromEgglog`](crate::wrap::FromEgglog) to turn them into typed nodes
+use crate::wrap::EgglogExpr;
+use egglog::{EGraph, TermDag, Value, ast::Parser};
+
+/// parse egglog term string into egglog expression
+pub fn parse_term(term: &str) -> EgglogExpr {
+    Parser::default()
+        .get_expr_from_string(None, term)
+        .unwrap_or_else(|e| panic!("failed to parse egglog term {term}: {e}"))
+}
+
+/// evaluate egglog expression such as `(F a b)` and extract the cheapest term of its eclass
+pub fn extract(egraph: &mut EGraph, expr: &str) -> Result<String, String> {
+    let expr = Parser::default()
+        .get_expr_from_string(None, expr)
+        .map_err(|e| e.to_string())?;
+    let (sort, value) = egraph.eval_expr(&expr).map_err(|e| e.to_string())?;
+    let mut termdag = TermDag::default();
+    let (_cost, term) = egraph
+        .extract(value, &mut termdag, &sort)
+        .map_err(|e| e.to_string())?;
+    Ok(termdag.to_string(&term))
+}
+
+/// eclass of a term, terms are equivalent iff they are in the same eclass
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EClassId {
+    pub sort: String,
+    pub value: Value,
+}
+
+/// evaluate egglog expression such as a node sym and return its canonical eclass
+///
+/// the egraph is rebuilt after rules are run, so equal ids mean the terms are proven equivalent
+pub fn eclass_of(egraph: &mut EGraph, expr: &str) -> Result<EClassId, String> {
+    let expr = Parser::default()
+        .get_expr_from_string(None, expr)
+        .map_err(|e| e.to_string())?;
+    let (sort, value) = egraph.eval_expr(&expr).map_err(|e| e.to_string())?;
+    Ok(EClassId {
+        sort: sort.name().to_string(),
+        value,
+    })
+}
+
+/// read at most max_size rows of function table after skipping the first offset rows,
+/// each row is returned as (call term, output term)
+///
+/// egglog always reads a table from its start, so the skipped rows are still extracted. pages read
+/// by separate calls may skip or repeat rows if the table is changed in between
+pub fn function_rows(
+    egraph: &mut EGraph,
+    func_name: &str,
+    offset: usize,
+    max_size: Option<usize>,
+) -> Result<Vec<(String, String)>, String> {
+    let n = max_size.map_or(usize::MAX, |max_size| offset.saturating_add(max_size));
+    let (rows, termdag) = egraph
+        .function_to_dag(func_name.into(), n)
+        .map_err(|e| e.to_string())?;
+    Ok(rows
+        .iter()
+        .skip(offset)
+        .map(|(call, output)| (termdag.to_string(call), termdag.to_string(output)))
+        .collect())
+}
+
+/// relation holding matches of a query, it only lives between `push` and `pop`
+const QUERY_TABLE: &str = "__query";
+
+/// run query facts such as `(= p (OffsetPoint (CurAnchorOf a) off))` and read at most max_size matches,
+/// each match is returned as call term `(__query a off)` of vars in order
+///
+/// vars are given with their sorts, matches are collected into a relation by a rule which is dropped
+/// with the relation afterwards, so the egraph is left as it was
+pub fn matches(
+    egraph: &mut EGraph,
+    facts: &str,
+    vars: &[(&str, &str)],
+    max_size: Option<usize>,
+) -> Result<Vec<String>, String> {
+    egraph
+        .parse_and_run_program(None, "(push)")
+        .map_err(|e| e.to_string())?;
+    let sorts = vars.iter().map(|(_, sort)| *sort).collect::<Vec<_>>();
+    let var_names = vars.iter().map(|(var, _)| *var).collect::<Vec<_>>();
+    let program = format!(
+        "(relation {QUERY_TABLE} ({})) (ruleset {QUERY_TABLE}) (rule ({facts}) (({QUERY_TABLE} {})) :ruleset {QUERY_TABLE}) (run {QUERY_TABLE} 1)",
+        sorts.join(" "),
+        var_names.join(" "),
+    );
+    let rows = egraph
+        .parse_and_run_program(None, &program)
+        .map_err(|e| e.to_string())
+        .and_then(|_| function_rows(egraph, QUERY_TABLE, 0, max_size));
+    egraph
+        .parse_and_run_program(None, "(pop)")
+        .map_err(|e| e.to_string())?;
+    Ok(rows?.into_iter().map(|(call, _)| call).collect())
+}
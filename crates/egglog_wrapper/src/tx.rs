@@ -1,12 +1,18 @@
-use crate::{collect_string_type_defs, wrap::*};
+use crate::{collect_string_type_defs, export, query, wrap::*};
 use dashmap::DashMap;
-use egglog::{EGraph, SerializeConfig, util::IndexSet};
-use std::{path::PathBuf, sync::Mutex};
+use egglog::{EGraph, util::IndexSet};
+use std::{
+    path::PathBuf,
+    sync::Mutex,
+    thread::{self, ThreadId},
+};
 
 pub struct TxNoVT {
     egraph: Mutex<EGraph>,
     map: DashMap<Sym, WorkAreaNode>,
     latest_map: DashMap<Sym, Sym>,
+    /// egglog programs collected by `transaction`, keyed by the thread running the transaction
+    buffers: DashMap<ThreadId, String>,
 }
 
 /// Tx without version ctl feature
@@ -21,6 +27,7 @@ impl TxNoVT {
             }),
             map: DashMap::default(),
             latest_map: DashMap::default(),
+            buffers: DashMap::default(),
         }
     }
     pub fn new() -> Self {
@@ -31,13 +38,37 @@ impl TxNoVT {
         let mut egraph = self.egraph.lock().unwrap();
         egraph.parse_and_run_program(None, s.as_str()).unwrap();
     }
+    /// run `f` in a transaction
+    ///
+    /// all egglog code sent inside `f` is collected into one program which is sent to egraph
+    /// once the outermost transaction ends, instead of being interpreted command by command.
+    /// transaction is scoped to the calling thread, egglog code sent by other threads meanwhile
+    /// is interpreted at once. If `f` panics, the collected program is still sent so that egraph
+    /// keeps up with the work area.
+    pub fn transaction<R>(&self, f: impl FnOnce() -> R) -> R {
+        let thread = thread::current().id();
+        if self.buffers.contains_key(&thread) {
+            return f();
+        }
+        self.buffers.insert(thread, String::new());
+        let _guard = TransactionGuard { tx: self, thread };
+        f()
+    }
+    /// evaluate egglog expression such as `(F a b)` and extract the cheapest term of its eclass,
+    /// see [`query::extract`]
+    pub fn extract_term(&self, expr: &str) -> Result<String, String> {
+        query::extract(&mut self.egraph.lock().unwrap(), expr)
+    }
     pub fn to_dot(&self, file_name: PathBuf) {
-        let egraph = self.egraph.lock().unwrap();
-        let serialized = egraph.serialize(SerializeConfig::default());
-        let dot_path = file_name.with_extension("dot");
-        serialized
-            .to_dot_file(dot_path.clone())
-            .unwrap_or_else(|_| panic!("Failed to write dot file to {dot_path:?}"));
+        export::egraph_to_dot(&self.egraph.lock().unwrap(), file_name);
+    }
+    /// write egraph in JSON format of egraph-serialize
+    pub fn to_json(&self, file_name: PathBuf) {
+        export::egraph_to_json(&self.egraph.lock().unwrap(), file_name);
+    }
+    /// write work area nodes with their version links as JSON
+    pub fn work_area_to_json(&self, file_name: PathBuf) {
+        export::work_area_to_json(self.map.iter(), file_name);
     }
     // collect all ancestors of cur_sym, without cur_sym
     pub fn collect_latest_ancestors(&self, cur_sym: Sym, index_set: &mut IndexSet<Sym>) {
@@ -180,20 +211,46 @@ impl TxNoVT {
     }
 }
 
+/// ends the outermost transaction of a thread when dropped, even by unwinding
+struct TransactionGuard<'a> {
+    tx: &'a TxNoVT,
+    thread: ThreadId,
+}
+
+impl Drop for TransactionGuard<'_> {
+    fn drop(&mut self) {
+        let Some((_, program)) = self.tx.buffers.remove(&self.thread) else {
+            return;
+        };
+        if program.is_empty() {
+            return;
+        }
+        if thread::panicking() {
+            // panicking again would abort, so failure is only logged
+            log::info!("{}", program);
+            let mut egraph = self.tx.egraph.lock().unwrap_or_else(|e| e.into_inner());
+            if let Err(e) = egraph.parse_and_run_program(None, &program) {
+                log::error!("failed to run program of panicked transaction: {e}");
+            }
+        } else {
+            self.tx.interpret(program);
+        }
+    }
+}
+
 unsafe impl Send for TxNoVT {}
 unsafe impl Sync for TxNoVT {}
 // MARK: Receiver
 impl Tx for TxNoVT {
     fn send(&self, received: TxCommand) {
-        match received {
-            TxCommand::StringCommand { string_command } => {
-                self.interpret(string_command);
-            }
-            TxCommand::NativeCommand { native_command } => {
-                let mut egraph = self.egraph.lock().unwrap();
-                // egraph.run_program(program);
-                todo!();
-            }
+        let program = match received {
+            TxCommand::StringCommand { string_command } => string_command,
+            // native command is rendered, so that it keeps its order among buffered commands
+            TxCommand::NativeCommand { native_command } => native_command.to_string(),
+        };
+        match self.buffers.get_mut(&thread::current().id()) {
+            Some(mut buffer) => buffer.push_str(&program),
+            None => self.interpret(program),
         }
     }
 
@@ -0,0 +1,209 @@
+use crate::{collect_string_type_defs, export, tx_vt::TxVT, wrap::*};
+use egglog::EGraph;
+use std::{
+    any::Any,
+    future::Future,
+    panic::{self, AssertUnwindSafe},
+    path::PathBuf,
+    sync::mpsc::{self, Sender},
+    thread,
+};
+use tokio::sync::oneshot;
+
+/// message consumed by egraph worker
+enum WorkerMsg {
+    Command(TxCommand),
+    /// answered once all messages sent before are processed, by the failures since last flush
+    Flush(oneshot::Sender<Result<(), String>>),
+    Run(Box<dyn FnOnce(&mut EGraph) + Send>),
+}
+
+/// run program on worker, failure is logged and kept for next flush rather than stopping worker
+fn run_program(egraph: &mut EGraph, program: &str, failures: &mut Vec<String>) {
+    log::info!("{}", program);
+    if let Err(e) = egraph.parse_and_run_program(None, program) {
+        log::error!("egraph worker failed to run {program}: {e}");
+        failures.push(format!("failed to run {program}: {e}"));
+    }
+}
+
+/// message of a caught panic
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => match payload.downcast::<&'static str>() {
+            Ok(message) => message.to_string(),
+            Err(_) => "panic with non string payload".to_owned(),
+        },
+    }
+}
+
+/// Tx with version ctl feature, egglog is run by a dedicated worker thread
+///
+/// version control is still done on the caller's thread, only the egglog programs produced by
+/// commits are pushed to the worker. So `commit` returns before egraph is rebuilt, use
+/// [`TxAsync::flush`] or [`TxAsync::flushed`] to wait for the egraph. Programs failed by egglog
+/// don't stop the worker, they are logged and reported by the next flush.
+pub struct TxAsync {
+    vt: TxVT,
+    sender: Sender<WorkerMsg>,
+}
+
+impl TxAsync {
+    pub fn new_with_type_defs(type_defs: String) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        thread::Builder::new()
+            .name("egraph-worker".to_owned())
+            .spawn(move || {
+                // EGraph is not Send, so it is created on the worker thread
+                let mut egraph = EGraph::default();
+                // failures since last flush
+                let mut failures = Vec::new();
+                run_program(&mut egraph, &type_defs, &mut failures);
+                for msg in receiver {
+                    match msg {
+                        WorkerMsg::Command(TxCommand::StringCommand { string_command }) => {
+                            run_program(&mut egraph, &string_command, &mut failures)
+                        }
+                        WorkerMsg::Command(TxCommand::NativeCommand { native_command }) => {
+                            run_program(&mut egraph, &native_command.to_string(), &mut failures)
+                        }
+                        WorkerMsg::Flush(done) => {
+                            let rst = if failures.is_empty() {
+                                Ok(())
+                            } else {
+                                Err(std::mem::take(&mut failures).join("\n"))
+                            };
+                            // receiver may have been dropped, nobody is waiting then
+                            let _ = done.send(rst);
+                        }
+                        WorkerMsg::Run(f) => f(&mut egraph),
+                    }
+                }
+            })
+            .expect("failed to spawn egraph worker");
+        let command_sender = sender.clone();
+        Self {
+            vt: TxVT::new_with_sink(move |command| {
+                command_sender
+                    .send(WorkerMsg::Command(command))
+                    .expect("egraph worker has stopped")
+            }),
+            sender,
+        }
+    }
+    pub fn new() -> Self {
+        Self::new_with_type_defs(collect_string_type_defs())
+    }
+    fn flush_marker(&self) -> oneshot::Receiver<Result<(), String>> {
+        let (done, receiver) = oneshot::channel();
+        self.sender
+            .send(WorkerMsg::Flush(done))
+            .expect("egraph worker has stopped");
+        receiver
+    }
+    /// block until all commands sent before are interpreted by egraph, return the failures of
+    /// commands since last flush
+    ///
+    /// must not be called inside async context, use [`TxAsync::flushed`] there
+    pub fn flush(&self) -> Result<(), String> {
+        self.flush_marker()
+            .blocking_recv()
+            .expect("egraph worker has stopped")
+    }
+    /// return a future which resolves once all commands sent before this call are interpreted,
+    /// to the failures of commands since last flush
+    pub fn flushed(&self) -> impl Future<Output = Result<(), String>> + Send + 'static {
+        let receiver = self.flush_marker();
+        async move { receiver.await.expect("egraph worker has stopped") }
+    }
+    /// run `f` on egraph after all commands sent before, and block until it returns
+    ///
+    /// panic of `f` is caught on the worker and returned as error, so the worker keeps running
+    /// with egraph as `f` left it
+    pub fn with_egraph<R: Send + 'static>(
+        &self,
+        f: impl FnOnce(&mut EGraph) -> R + Send + 'static,
+    ) -> Result<R, String> {
+        let (rst_sender, rst_receiver) = mpsc::channel();
+        self.sender
+            .send(WorkerMsg::Run(Box::new(move |egraph| {
+                let rst = panic::catch_unwind(AssertUnwindSafe(|| f(egraph))).map_err(|payload| {
+                    format!("panicked on egraph worker: {}", panic_message(payload))
+                });
+                let _ = rst_sender.send(rst);
+            })))
+            .expect("egraph worker has stopped");
+        rst_receiver.recv().expect("egraph worker has stopped")
+    }
+    pub fn to_dot(&self, file_name: PathBuf) {
+        self.with_egraph(move |egraph| export::egraph_to_dot(egraph, file_name))
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+    /// write egraph in JSON format of egraph-serialize
+    pub fn to_json(&self, file_name: PathBuf) {
+        self.with_egraph(move |egraph| export::egraph_to_json(egraph, file_name))
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+    /// write work area nodes with their version links as JSON
+    pub fn work_area_to_json(&self, file_name: PathBuf) {
+        self.vt.work_area_to_json(file_name);
+    }
+}
+
+unsafe impl Send for TxAsync {}
+unsafe impl Sync for TxAsync {}
+impl VersionCtl for TxAsync {
+    fn locate_latest(&self, node: Sym) -> Sym {
+        self.vt.locate_latest(node)
+    }
+    fn locate_next(&self, node: Sym) -> Sym {
+        self.vt.locate_next(node)
+    }
+    fn locate_prev(&self, node: Sym) -> Sym {
+        self.vt.locate_prev(node)
+    }
+    fn set_latest(&self, node: &mut Sym) {
+        self.vt.set_latest(node)
+    }
+    fn set_next(&self, node: &mut Sym) {
+        self.vt.set_next(node)
+    }
+    fn set_prev(&self, node: &mut Sym) {
+        self.vt.set_prev(node)
+    }
+}
+
+// MARK: Receiver
+impl Tx for TxAsync {
+    fn send(&self, received: TxCommand) {
+        self.vt.send(received);
+    }
+
+    fn on_new(&self, node: &(impl EgglogNode + 'static)) {
+        self.vt.on_new(node);
+    }
+
+    fn on_set(&self, node: &mut (impl EgglogNode + 'static)) {
+        self.vt.on_set(node);
+    }
+
+    fn on_func_set<'a, F: EgglogFunc>(
+        &self,
+        input: <F::Input as EgglogFuncInputs>::Ref<'a>,
+        output: <F::Output as crate::wrap::EgglogFuncOutput>::Ref<'a>,
+    ) {
+        self.vt.on_func_set::<F>(input, output);
+    }
+}
+
+impl TxCommit for TxAsync {
+    /// same as commit of [`TxVT`] but returns without waiting for egraph
+    fn on_commit<T: EgglogNode>(&self, commit_root: &T) {
+        self.vt.on_commit(commit_root);
+    }
+
+    fn on_stage<T: EgglogNode + ?Sized>(&self, node: &T) {
+        self.vt.on_stage(node);
+    }
+}
@@ -1,5 +1,5 @@
-use crate::{collect_string_type_defs, wrap::*};
-use egglog::{EGraph, SerializeConfig, ast::Command};
+use crate::{collect_string_type_defs, export, wrap::*};
+use egglog::{EGraph, ast::Command};
 use std::{path::PathBuf, sync::Mutex};
 
 pub struct TxMinimal {
@@ -35,12 +35,11 @@ impl TxMinimal {
         egraph.parse_and_run_program(None, s.as_str()).unwrap();
     }
     pub fn to_dot(&self, file_name: PathBuf) {
-        let egraph = self.egraph.lock().unwrap();
-        let serialized = egraph.serialize(SerializeConfig::default());
-        let dot_path = file_name.with_extension("dot");
-        serialized
-            .to_dot_file(dot_path.clone())
-            .unwrap_or_else(|_| panic!("Failed to write dot file to {dot_path:?}"));
+        export::egraph_to_dot(&self.egraph.lock().unwrap(), file_name);
+    }
+    /// write egraph in JSON format of egraph-serialize
+    pub fn to_json(&self, file_name: PathBuf) {
+        export::egraph_to_json(&self.egraph.lock().unwrap(), file_name);
     }
 }
 
@@ -0,0 +1,219 @@
+use crate::{query, wrap::*};
+use std::{collections::HashMap, sync::Mutex};
+
+/// call received by [`TxMock`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MockCall {
+    Send { command: String },
+    New { sym: Sym, egglog: String },
+    Set { sym: Sym, egglog: String },
+    FuncSet { func: &'static str, input: Vec<Sym>, output: Sym },
+    Stage { sym: Sym },
+    Commit { sym: Sym },
+    FuncGet { func: &'static str, input: Vec<Sym> },
+    FuncsGet { func: &'static str, offset: usize, max_size: Option<usize> },
+    Pull { sym: Sym },
+}
+
+impl MockCall {
+    /// sym of node this call is about, None for calls not about a single node
+    pub fn sym(&self) -> Option<Sym> {
+        match self {
+            MockCall::New { sym, .. }
+            | MockCall::Set { sym, .. }
+            | MockCall::Stage { sym }
+            | MockCall::Commit { sym }
+            | MockCall::Pull { sym } => Some(*sym),
+            _ => None,
+        }
+    }
+}
+
+/// Tx without egraph, every call is appended to a log which could be inspected by tests
+///
+/// version control is an identity, nodes are never renamed. Rx answers are scripted by
+/// [`TxMock::script_func_get`] and [`TxMock::script_funcs_get`] in egglog term syntax.
+#[derive(Default)]
+pub struct TxMock {
+    calls: Mutex<Vec<MockCall>>,
+    /// function name -> output term
+    func_get_scripts: Mutex<HashMap<&'static str, String>>,
+    /// function name -> rows of (call term, output term)
+    funcs_get_scripts: Mutex<HashMap<&'static str, Vec<(String, String)>>>,
+}
+
+impl TxMock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    fn record(&self, call: MockCall) {
+        log::debug!("{:?}", call);
+        self.calls.lock().unwrap().push(call);
+    }
+    /// all calls received so far, in order
+    pub fn calls(&self) -> Vec<MockCall> {
+        self.calls.lock().unwrap().clone()
+    }
+    /// return all calls received so far and clear the log
+    pub fn take_calls(&self) -> Vec<MockCall> {
+        std::mem::take(&mut *self.calls.lock().unwrap())
+    }
+    /// calls about node `sym`, in order
+    pub fn calls_of(&self, sym: Sym) -> Vec<MockCall> {
+        self.calls
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|call| call.sym() == Some(sym))
+            .cloned()
+            .collect()
+    }
+    /// egglog statements of all created nodes, in order of creation
+    pub fn created(&self) -> Vec<String> {
+        self.calls
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|call| match call {
+                MockCall::New { egglog, .. } => Some(egglog.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+    /// answer every `on_func_get` of F by `output_term`, such as `(V (vec-of (End)))`
+    pub fn script_func_get<F: EgglogFunc>(&self, output_term: impl Into<String>) {
+        self.func_get_scripts
+            .lock()
+            .unwrap()
+            .insert(F::FUNC_NAME, output_term.into());
+    }
+    /// answer every `on_funcs_get` of F by `rows` of (call term, output term)
+    pub fn script_funcs_get<F: EgglogFunc>(&self, rows: Vec<(String, String)>) {
+        self.funcs_get_scripts
+            .lock()
+            .unwrap()
+            .insert(F::FUNC_NAME, rows);
+    }
+}
+
+unsafe impl Send for TxMock {}
+unsafe impl Sync for TxMock {}
+impl VersionCtl for TxMock {
+    fn locate_latest(&self, node: Sym) -> Sym {
+        node
+    }
+    fn locate_next(&self, node: Sym) -> Sym {
+        node
+    }
+    fn locate_prev(&self, node: Sym) -> Sym {
+        node
+    }
+    fn set_latest(&self, _node: &mut Sym) {}
+    fn set_next(&self, _node: &mut Sym) {}
+    fn set_prev(&self, _node: &mut Sym) {}
+}
+
+// MARK: Receiver
+impl Tx for TxMock {
+    fn send(&self, received: TxCommand) {
+        let command = match received {
+            TxCommand::StringCommand { string_command } => string_command,
+            TxCommand::NativeCommand { native_command } => native_command.to_string(),
+        };
+        self.record(MockCall::Send { command });
+    }
+
+    fn on_new(&self, node: &(impl EgglogNode + 'static)) {
+        self.record(MockCall::New {
+            sym: node.cur_sym(),
+            egglog: node.to_egglog(),
+        });
+    }
+
+    fn on_set(&self, node: &mut (impl EgglogNode + 'static)) {
+        self.record(MockCall::Set {
+            sym: node.cur_sym(),
+            egglog: node.to_egglog(),
+        });
+    }
+
+    fn on_func_set<'a, F: EgglogFunc>(
+        &self,
+        input: <F::Input as EgglogFuncInputs>::Ref<'a>,
+        output: <F::Output as crate::wrap::EgglogFuncOutput>::Ref<'a>,
+    ) {
+        self.record(MockCall::FuncSet {
+            func: F::FUNC_NAME,
+            input: input.as_nodes().iter().map(|x| x.cur_sym()).collect(),
+            output: output.as_node().cur_sym(),
+        });
+    }
+}
+
+impl TxCommit for TxMock {
+    fn on_commit<T: EgglogNode>(&self, commit_root: &T) {
+        self.record(MockCall::Commit {
+            sym: commit_root.cur_sym(),
+        });
+    }
+
+    fn on_stage<T: EgglogNode + ?Sized>(&self, node: &T) {
+        self.record(MockCall::Stage {
+            sym: node.cur_sym(),
+        });
+    }
+}
+
+impl Rx for TxMock {
+    fn on_func_get<'a, F: EgglogFunc>(
+        &self,
+        input: <F::Input as EgglogFuncInputs>::Ref<'a>,
+    ) -> F::Output
+    where
+        F::Output: FromEgglog,
+    {
+        self.record(MockCall::FuncGet {
+            func: F::FUNC_NAME,
+            input: input.as_nodes().iter().map(|x| x.cur_sym()).collect(),
+        });
+        let term = self
+            .func_get_scripts
+            .lock()
+            .unwrap()
+            .get(F::FUNC_NAME)
+            .cloned()
+            .unwrap_or_else(|| panic!("get of {} is not scripted", F::FUNC_NAME));
+        // lock is released before nodes of the term are created, they are also logged
+        <F::Output as FromEgglog>::from_egglog(&query::parse_term(&term))
+    }
+
+    fn on_funcs_get_terms(
+        &self,
+        func_name: &'static str,
+        offset: usize,
+        max_size: Option<usize>,
+    ) -> Vec<(String, String)> {
+        self.record(MockCall::FuncsGet {
+            func: func_name,
+            offset,
+            max_size,
+        });
+        let rows = self
+            .funcs_get_scripts
+            .lock()
+            .unwrap()
+            .get(func_name)
+            .cloned()
+            .unwrap_or_else(|| panic!("get_all of {func_name} is not scripted"));
+        rows.into_iter()
+            .skip(offset)
+            .take(max_size.unwrap_or(usize::MAX))
+            .collect()
+    }
+
+    fn on_pull(&self, node: &(impl EgglogNode + 'static)) {
+        self.record(MockCall::Pull {
+            sym: node.cur_sym(),
+        });
+    }
+}
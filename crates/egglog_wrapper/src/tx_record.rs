@@ -0,0 +1,201 @@
+use crate::{collect_string_type_defs, export, query, tx_vt::TxVT, wrap::*};
+use egglog::EGraph;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+const TRANSCRIPT_HEADER: &str = "; egglog transcript recorded by egglog_wrapper\n";
+
+/// egraph together with every egglog program it has run
+struct Recorder {
+    egraph: Mutex<EGraph>,
+    transcript: Mutex<String>,
+}
+
+unsafe impl Send for Recorder {}
+unsafe impl Sync for Recorder {}
+
+impl Recorder {
+    fn new() -> Self {
+        Self {
+            egraph: Mutex::new(EGraph::default()),
+            transcript: Mutex::new(TRANSCRIPT_HEADER.to_owned()),
+        }
+    }
+    fn run(&self, program: &str) -> Result<(), String> {
+        log::info!("{}", program);
+        self.egraph
+            .lock()
+            .unwrap()
+            .parse_and_run_program(None, program)
+            .map_err(|e| e.to_string())?;
+        let mut transcript = self.transcript.lock().unwrap();
+        transcript.push_str(program);
+        transcript.push('\n');
+        Ok(())
+    }
+}
+
+/// Tx with version ctl feature which records every egglog program it runs
+///
+/// the transcript starts with type definitions and can be run by plain `egglog`, or replayed into
+/// a fresh Tx by [`TxRecord::replay`] to check that it reproduces the same egraph.
+pub struct TxRecord {
+    vt: TxVT,
+    recorder: Arc<Recorder>,
+}
+
+impl TxRecord {
+    pub fn new_with_type_defs(type_defs: String) -> Self {
+        let recorder = Arc::new(Recorder::new());
+        recorder.run(&type_defs).unwrap();
+        let sink_recorder = recorder.clone();
+        Self {
+            vt: TxVT::new_with_sink(move |command| {
+                let program = match command {
+                    TxCommand::StringCommand { string_command } => string_command,
+                    TxCommand::NativeCommand { native_command } => native_command.to_string(),
+                };
+                sink_recorder.run(&program).unwrap();
+            }),
+            recorder,
+        }
+    }
+    pub fn new() -> Self {
+        Self::new_with_type_defs(collect_string_type_defs())
+    }
+    /// create a Tx whose egraph is built by running `transcript`
+    ///
+    /// nodes of the transcript are not known by the version control of returned Tx,
+    /// it's meant to be compared with the recording one by [`TxRecord::assert_same_egraph`]
+    pub fn replay(transcript: &str) -> Result<Self, String> {
+        let recorder = Arc::new(Recorder::new());
+        let body = transcript
+            .strip_prefix(TRANSCRIPT_HEADER)
+            .unwrap_or(transcript);
+        recorder.run(body)?;
+        Ok(Self {
+            vt: TxVT::new_with_sink(|_| panic!("replayed Tx is read only")),
+            recorder,
+        })
+    }
+    /// replay the transcript saved at `path`
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let transcript = fs::read_to_string(path)?;
+        Self::replay(&transcript).map_err(io::Error::other)
+    }
+    /// egglog programs run so far, type definitions included
+    pub fn transcript(&self) -> String {
+        self.recorder.transcript.lock().unwrap().clone()
+    }
+    /// write transcript to `path`, it's conventionally suffixed by `.egg`
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.transcript())
+    }
+    /// rows of all function tables as (call term, output term), keyed by function name
+    ///
+    /// rows are read as extracted terms rather than by ids, so egraphs are compared regardless of
+    /// the ids assigned to their nodes
+    fn snapshot(&self) -> BTreeMap<String, BTreeSet<(String, String)>> {
+        let mut egraph = self.recorder.egraph.lock().unwrap();
+        let func_names = egraph
+            .functions
+            .keys()
+            .map(|func_name| func_name.to_string())
+            .collect::<Vec<_>>();
+        func_names
+            .into_iter()
+            .map(|func_name| {
+                let rows = query::function_rows(&mut egraph, &func_name, 0, None)
+                    .unwrap_or_else(|e| panic!("failed to read rows of {func_name}: {e}"));
+                (func_name, rows.into_iter().collect())
+            })
+            .collect()
+    }
+    /// panic if function tables of two egraphs don't have the same rows
+    pub fn assert_same_egraph(&self, other: &TxRecord) {
+        let (this, other) = (self.snapshot(), other.snapshot());
+        for (func_name, rows) in &this {
+            match other.get(func_name) {
+                Some(other_rows) => assert_eq!(rows, other_rows, "rows of {func_name} differ"),
+                None => panic!("function {func_name} is missing in other egraph"),
+            }
+        }
+        for func_name in other.keys() {
+            assert!(
+                this.contains_key(func_name),
+                "function {func_name} is missing in this egraph"
+            );
+        }
+    }
+    pub fn to_dot(&self, file_name: PathBuf) {
+        export::egraph_to_dot(&self.recorder.egraph.lock().unwrap(), file_name);
+    }
+    /// write egraph in JSON format of egraph-serialize
+    pub fn to_json(&self, file_name: PathBuf) {
+        export::egraph_to_json(&self.recorder.egraph.lock().unwrap(), file_name);
+    }
+    /// write work area nodes with their version links as JSON
+    pub fn work_area_to_json(&self, file_name: PathBuf) {
+        self.vt.work_area_to_json(file_name);
+    }
+}
+
+unsafe impl Send for TxRecord {}
+unsafe impl Sync for TxRecord {}
+impl VersionCtl for TxRecord {
+    fn locate_latest(&self, node: Sym) -> Sym {
+        self.vt.locate_latest(node)
+    }
+    fn locate_next(&self, node: Sym) -> Sym {
+        self.vt.locate_next(node)
+    }
+    fn locate_prev(&self, node: Sym) -> Sym {
+        self.vt.locate_prev(node)
+    }
+    fn set_latest(&self, node: &mut Sym) {
+        self.vt.set_latest(node)
+    }
+    fn set_next(&self, node: &mut Sym) {
+        self.vt.set_next(node)
+    }
+    fn set_prev(&self, node: &mut Sym) {
+        self.vt.set_prev(node)
+    }
+}
+
+// MARK: Receiver
+impl Tx for TxRecord {
+    fn send(&self, received: TxCommand) {
+        self.vt.send(received);
+    }
+
+    fn on_new(&self, node: &(impl EgglogNode + 'static)) {
+        self.vt.on_new(node);
+    }
+
+    fn on_set(&self, node: &mut (impl EgglogNode + 'static)) {
+        self.vt.on_set(node);
+    }
+
+    fn on_func_set<'a, F: EgglogFunc>(
+        &self,
+        input: <F::Input as EgglogFuncInputs>::Ref<'a>,
+        output: <F::Output as crate::wrap::EgglogFuncOutput>::Ref<'a>,
+    ) {
+        self.vt.on_func_set::<F>(input, output);
+    }
+}
+
+impl TxCommit for TxRecord {
+    fn on_commit<T: EgglogNode>(&self, commit_root: &T) {
+        self.vt.on_commit(commit_root);
+    }
+
+    fn on_stage<T: EgglogNode + ?Sized>(&self, node: &T) {
+        self.vt.on_stage(node);
+    }
+}
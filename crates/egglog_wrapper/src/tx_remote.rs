@@ -0,0 +1,474 @@
+//! Tx whose egraph is owned by an egraph server, connected by tcp or unix domain socket
+//!
+//! # Protocol (version 2)
+//!
+//! Every message is a frame of an ascii header line followed by an utf-8 payload
+//! ```text
+//! <kind> <payload length in bytes>\n<payload>
+//! ```
+//! Client sends request frames and server answers each of them, in order, with either an
+//! `ok` frame carrying the result or an `err` frame carrying the error message. Payloads longer
+//! than [`MAX_FRAME_LEN`] are rejected as invalid data and close the connection, a result which
+//! doesn't fit in a frame is answered by `err`, read such tables by pages.
+//!
+//! | request     | payload                                         | result of `ok`                   |
+//! |-------------|-------------------------------------------------|----------------------------------|
+//! | `hello`     | `<protocol version>\n<type defs>`               | empty                            |
+//! | `command`   | egglog program                                  | empty                            |
+//! | `extract`   | egglog expression such as `(F a b)`             | cheapest term of the eclass      |
+//! | `funcs_get` | `<function name> <offset> <max size or "all">`  | list of call term, output term.. |
+//!
+//! `hello` must be the first request of a connection. The first `hello` received by a server
+//! defines the types of its egraph, later clients must send identical type definitions.
+//!
+//! A list is encoded as the concatenation of its items, each item being `<length>\n<item>`.
+//!
+//! There is no request for `Rx::on_pull`, nodes can't be updated in place by the server, so
+//! [`TxRemote`] only checks that the node is known to the server. Use [`TxRemote::extract`] to
+//! read node's eclass as a new node.
+use crate::{collect_string_type_defs, query, tx_vt::TxVT, wrap::*};
+use egglog::EGraph;
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    path::PathBuf,
+    sync::{
+        Arc, Mutex,
+        mpsc::{self, Receiver, Sender},
+    },
+    thread,
+};
+
+pub const PROTOCOL_VERSION: u32 = 2;
+/// max payload length of a frame in bytes
+pub const MAX_FRAME_LEN: usize = 64 << 20;
+/// max length of a frame header line in bytes, enough for any kind and length
+const MAX_HEADER_LEN: u64 = 64;
+
+/// byte stream connecting TxRemote and egraph server
+pub trait Stream: Read + Write + Send {}
+impl<S: Read + Write + Send> Stream for S {}
+
+fn invalid_data(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn write_frame(stream: &mut impl Write, kind: &str, payload: &str) -> io::Result<()> {
+    if payload.len() > MAX_FRAME_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "payload of {} bytes exceeds {MAX_FRAME_LEN} bytes",
+                payload.len()
+            ),
+        ));
+    }
+    writeln!(stream, "{} {}", kind, payload.len())?;
+    stream.write_all(payload.as_bytes())?;
+    stream.flush()
+}
+
+/// return None if the stream is closed
+fn read_frame(stream: &mut impl BufRead) -> io::Result<Option<(String, String)>> {
+    let mut header = String::new();
+    let mut header_stream = stream.by_ref().take(MAX_HEADER_LEN);
+    if header_stream.read_line(&mut header)? == 0 {
+        return Ok(None);
+    }
+    if !header.ends_with('\n') {
+        return Err(invalid_data(format!("malformed frame header {header:?}")));
+    }
+    let (kind, len) = header
+        .trim_end()
+        .split_once(' ')
+        .ok_or_else(|| invalid_data(format!("malformed frame header {header:?}")))?;
+    let len = len
+        .parse::<usize>()
+        .map_err(|_| invalid_data(format!("malformed frame header {header:?}")))?;
+    if len > MAX_FRAME_LEN {
+        return Err(invalid_data(format!(
+            "frame of {len} bytes exceeds {MAX_FRAME_LEN} bytes"
+        )));
+    }
+    let mut payload = vec![0; len];
+    stream.read_exact(&mut payload)?;
+    let payload = String::from_utf8(payload).map_err(|e| invalid_data(e.to_string()))?;
+    Ok(Some((kind.to_owned(), payload)))
+}
+
+fn encode_list<'a>(items: impl IntoIterator<Item = &'a String>) -> String {
+    items.into_iter().fold(String::new(), |s, item| {
+        s + &item.len().to_string() + "\n" + item
+    })
+}
+
+fn decode_list(mut payload: &str) -> io::Result<Vec<String>> {
+    let mut items = Vec::new();
+    while !payload.is_empty() {
+        let (len, rest) = payload
+            .split_once('\n')
+            .ok_or_else(|| invalid_data("malformed list".to_owned()))?;
+        let len = len
+            .parse::<usize>()
+            .map_err(|_| invalid_data("malformed list".to_owned()))?;
+        if !rest.is_char_boundary(len) {
+            return Err(invalid_data("malformed list".to_owned()));
+        }
+        let (item, rest) = rest.split_at(len);
+        items.push(item.to_owned());
+        payload = rest;
+    }
+    Ok(items)
+}
+
+struct Connection {
+    stream: BufReader<Box<dyn Stream>>,
+}
+
+impl Connection {
+    fn request(&mut self, kind: &str, payload: &str) -> io::Result<Result<String, String>> {
+        write_frame(self.stream.get_mut(), kind, payload)?;
+        match read_frame(&mut self.stream)? {
+            Some((kind, payload)) if kind == "ok" => Ok(Ok(payload)),
+            Some((kind, payload)) if kind == "err" => Ok(Err(payload)),
+            Some((kind, _)) => Err(invalid_data(format!("unexpected response {kind}"))),
+            None => Err(io::ErrorKind::UnexpectedEof.into()),
+        }
+    }
+}
+
+/// Tx with version ctl feature whose egraph is owned by an egraph server
+///
+/// version control is done locally, egglog programs and queries of terms are sent to the
+/// server.
+pub struct TxRemote {
+    vt: TxVT,
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl TxRemote {
+    pub fn connect_with_type_defs(stream: impl Stream + 'static, type_defs: String) -> io::Result<Self> {
+        let mut conn = Connection {
+            stream: BufReader::new(Box::new(stream)),
+        };
+        conn.request("hello", &format!("{PROTOCOL_VERSION}\n{type_defs}"))?
+            .map_err(io::Error::other)?;
+        let conn = Arc::new(Mutex::new(conn));
+        let sink_conn = conn.clone();
+        Ok(Self {
+            vt: TxVT::new_with_sink(move |command| {
+                let program = match command {
+                    TxCommand::StringCommand { string_command } => string_command,
+                    TxCommand::NativeCommand { native_command } => native_command.to_string(),
+                };
+                sink_conn
+                    .lock()
+                    .unwrap()
+                    .request("command", &program)
+                    .expect("lost connection to egraph server")
+                    .unwrap_or_else(|e| panic!("egraph server failed to run {program}: {e}"));
+            }),
+            conn,
+        })
+    }
+    pub fn connect(stream: impl Stream + 'static) -> io::Result<Self> {
+        Self::connect_with_type_defs(stream, collect_string_type_defs())
+    }
+    pub fn connect_tcp(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        Self::connect(TcpStream::connect(addr)?)
+    }
+    #[cfg(unix)]
+    pub fn connect_unix(path: impl AsRef<std::path::Path>) -> io::Result<Self> {
+        Self::connect(std::os::unix::net::UnixStream::connect(path)?)
+    }
+    fn request(&self, kind: &str, payload: &str) -> Result<String, String> {
+        self.conn
+            .lock()
+            .unwrap()
+            .request(kind, payload)
+            .expect("lost connection to egraph server")
+    }
+    /// evaluate egglog expression such as `(F a b)` on the server and extract the cheapest term of
+    /// its eclass
+    pub fn extract_term(&self, expr: &str) -> Result<String, String> {
+        self.request("extract", expr)
+    }
+    /// read at most max_size rows of function table from the server, starting from row offset,
+    /// see [`query::function_rows`]
+    pub fn function_rows(
+        &self,
+        func_name: &str,
+        offset: usize,
+        max_size: Option<usize>,
+    ) -> Result<Vec<(String, String)>, String> {
+        let max_size = max_size.map_or("all".to_owned(), |max_size| max_size.to_string());
+        let payload = self.request("funcs_get", &format!("{func_name} {offset} {max_size}"))?;
+        let items = decode_list(&payload)
+            .map_err(|e| format!("protocol error in response of funcs_get: {e}"))?;
+        if items.len() % 2 != 0 {
+            return Err(format!(
+                "protocol error in response of funcs_get: odd number of items {}",
+                items.len()
+            ));
+        }
+        Ok(items
+            .chunks_exact(2)
+            .map(|row| (row[0].clone(), row[1].clone()))
+            .collect())
+    }
+    /// extract the cheapest term of node's eclass from the server
+    pub fn extract<N: EgglogNode + FromEgglog>(&self, node: &N) -> N {
+        let term = self
+            .extract_term(node.cur_sym().as_str())
+            .unwrap_or_else(|e| panic!("failed to extract {}: {e}", node.cur_sym()));
+        N::from_egglog(&query::parse_term(&term))
+    }
+    /// write work area nodes with their version links as JSON, the egraph is kept by the server
+    pub fn work_area_to_json(&self, file_name: PathBuf) {
+        self.vt.work_area_to_json(file_name);
+    }
+}
+
+unsafe impl Send for TxRemote {}
+unsafe impl Sync for TxRemote {}
+impl VersionCtl for TxRemote {
+    fn locate_latest(&self, node: Sym) -> Sym {
+        self.vt.locate_latest(node)
+    }
+    fn locate_next(&self, node: Sym) -> Sym {
+        self.vt.locate_next(node)
+    }
+    fn locate_prev(&self, node: Sym) -> Sym {
+        self.vt.locate_prev(node)
+    }
+    fn set_latest(&self, node: &mut Sym) {
+        self.vt.set_latest(node)
+    }
+    fn set_next(&self, node: &mut Sym) {
+        self.vt.set_next(node)
+    }
+    fn set_prev(&self, node: &mut Sym) {
+        self.vt.set_prev(node)
+    }
+}
+
+// MARK: Receiver
+impl Tx for TxRemote {
+    fn send(&self, received: TxCommand) {
+        self.vt.send(received);
+    }
+
+    fn on_new(&self, node: &(impl EgglogNode + 'static)) {
+        self.vt.on_new(node);
+    }
+
+    fn on_set(&self, node: &mut (impl EgglogNode + 'static)) {
+        self.vt.on_set(node);
+    }
+
+    fn on_func_set<'a, F: EgglogFunc>(
+        &self,
+        input: <F::Input as EgglogFuncInputs>::Ref<'a>,
+        output: <F::Output as crate::wrap::EgglogFuncOutput>::Ref<'a>,
+    ) {
+        self.vt.on_func_set::<F>(input, output);
+    }
+}
+
+impl TxCommit for TxRemote {
+    fn on_commit<T: EgglogNode>(&self, commit_root: &T) {
+        self.vt.on_commit(commit_root);
+    }
+
+    fn on_stage<T: EgglogNode + ?Sized>(&self, node: &T) {
+        self.vt.on_stage(node);
+    }
+}
+
+impl Rx for TxRemote {
+    fn on_func_get<'a, F: EgglogFunc>(
+        &self,
+        input: <F::Input as EgglogFuncInputs>::Ref<'a>,
+    ) -> F::Output
+    where
+        F::Output: FromEgglog,
+    {
+        let call = func_call_term::<F>(&input.as_nodes());
+        let term = self
+            .extract_term(&call)
+            .unwrap_or_else(|e| panic!("failed to get {call}: {e}"));
+        <F::Output as FromEgglog>::from_egglog(&query::parse_term(&term))
+    }
+
+    fn on_funcs_get_terms(
+        &self,
+        func_name: &'static str,
+        offset: usize,
+        max_size: Option<usize>,
+    ) -> Vec<(String, String)> {
+        self.function_rows(func_name, offset, max_size)
+            .unwrap_or_else(|e| panic!("failed to get rows of {func_name}: {e}"))
+    }
+
+    /// check that node's eclass is on the server, panic if node is not committed to it
+    ///
+    /// pull is not supported by the protocol, node can't be updated in place, use
+    /// [`TxRemote::extract`] to read it as a node
+    fn on_pull(&self, node: &(impl EgglogNode + 'static)) {
+        let term = self
+            .extract_term(node.cur_sym().as_str())
+            .unwrap_or_else(|e| panic!("failed to pull {}: {e}", node.cur_sym()));
+        log::debug!("pulled {} as {}", node.cur_sym(), term);
+    }
+}
+
+// MARK: Server
+type Request = (String, String, Sender<Result<String, String>>);
+
+/// serve egraph to every connection of `incoming`, never returns unless `incoming` ends
+///
+/// usage:
+/// ```ignore
+/// let listener = TcpListener::bind("127.0.0.1:7878").unwrap();
+/// serve(listener.incoming());
+/// ```
+pub fn serve<S: Stream + 'static>(incoming: impl IntoIterator<Item = io::Result<S>>) {
+    let (sender, receiver) = mpsc::channel();
+    thread::Builder::new()
+        .name("egraph-server".to_owned())
+        .spawn(move || run_egraph(receiver))
+        .expect("failed to spawn egraph thread");
+    for stream in incoming {
+        match stream {
+            Ok(stream) => {
+                let sender = sender.clone();
+                thread::spawn(move || {
+                    if let Err(e) = serve_connection(stream, sender) {
+                        log::warn!("connection closed: {e}");
+                    }
+                });
+            }
+            Err(e) => log::warn!("failed to accept connection: {e}"),
+        }
+    }
+}
+
+fn serve_connection(stream: impl Stream, egraph: Sender<Request>) -> io::Result<()> {
+    let mut stream = BufReader::new(stream);
+    let mut greeted = false;
+    while let Some((kind, payload)) = read_frame(&mut stream)? {
+        let rst = if !greeted && kind != "hello" {
+            Err(format!("expect hello before {kind}"))
+        } else {
+            request_egraph(&egraph, kind.clone(), payload)
+        };
+        greeted |= kind == "hello" && rst.is_ok();
+        let rst = rst.and_then(|payload| {
+            if payload.len() > MAX_FRAME_LEN {
+                Err(format!(
+                    "result of {} bytes exceeds {MAX_FRAME_LEN} bytes",
+                    payload.len()
+                ))
+            } else {
+                Ok(payload)
+            }
+        });
+        match rst {
+            Ok(payload) => write_frame(stream.get_mut(), "ok", &payload)?,
+            Err(e) => write_frame(stream.get_mut(), "err", &e)?,
+        }
+    }
+    Ok(())
+}
+
+/// pass request to egraph thread and wait for its result, a stopped egraph thread fails the
+/// request rather than the server
+fn request_egraph(
+    egraph: &Sender<Request>,
+    kind: String,
+    payload: String,
+) -> Result<String, String> {
+    let (reply, rst) = mpsc::channel();
+    egraph
+        .send((kind, payload, reply))
+        .map_err(|_| "egraph thread has stopped".to_owned())?;
+    rst.recv()
+        .map_err(|_| "egraph thread has stopped".to_owned())?
+}
+
+/// EGraph is not Send, so all requests are handled by the thread creating it
+fn run_egraph(requests: Receiver<Request>) {
+    let mut egraph = EGraph::default();
+    let mut type_defs = None;
+    for (kind, payload, reply) in requests {
+        let rst = handle_request(&mut egraph, &mut type_defs, &kind, &payload);
+        if let Err(e) = &rst {
+            log::warn!("{kind} failed: {e}");
+        }
+        // client may have disconnected, nobody is waiting then
+        let _ = reply.send(rst);
+    }
+}
+
+fn handle_request(
+    egraph: &mut EGraph,
+    type_defs: &mut Option<String>,
+    kind: &str,
+    payload: &str,
+) -> Result<String, String> {
+    match kind {
+        "hello" => {
+            let (version, defs) = payload
+                .split_once('\n')
+                .ok_or_else(|| "malformed hello".to_owned())?;
+            if version != PROTOCOL_VERSION.to_string() {
+                return Err(format!(
+                    "protocol version {version} is not supported, server speaks {PROTOCOL_VERSION}"
+                ));
+            }
+            match type_defs {
+                Some(existing) if existing.as_str() == defs => Ok(String::new()),
+                Some(_) => Err("egraph is already defined by different type definitions".to_owned()),
+                None => {
+                    log::info!("{}", defs);
+                    egraph
+                        .parse_and_run_program(None, defs)
+                        .map_err(|e| e.to_string())?;
+                    *type_defs = Some(defs.to_owned());
+                    Ok(String::new())
+                }
+            }
+        }
+        "command" => {
+            log::info!("{}", payload);
+            egraph
+                .parse_and_run_program(None, payload)
+                .map(|_| String::new())
+                .map_err(|e| e.to_string())
+        }
+        "extract" => query::extract(egraph, payload),
+        "funcs_get" => {
+            let [func_name, offset, max_size]: [&str; 3] = payload
+                .splitn(3, ' ')
+                .collect::<Vec<_>>()
+                .try_into()
+                .map_err(|_| "malformed funcs_get".to_owned())?;
+            let offset = offset
+                .parse()
+                .map_err(|_| format!("malformed offset {offset}"))?;
+            let max_size = match max_size {
+                "all" => None,
+                max_size => Some(
+                    max_size
+                        .parse()
+                        .map_err(|_| format!("malformed max size {max_size}"))?,
+                ),
+            };
+            let rows = query::function_rows(egraph, func_name, offset, max_size)?;
+            Ok(encode_list(
+                rows.iter().flat_map(|(call, output)| [call, output]),
+            ))
+        }
+        _ => Err(format!("unknown request {kind}")),
+    }
+}
@@ -1,11 +1,18 @@
-use crate::{collect_string_type_defs, wrap::*};
+use crate::{
+    check::{self, CheckFailure, Fact},
+    collect_string_type_defs,
+    export,
+    extract::{self, AnnotatedCost, Extractor, SerializedEGraph},
+    pattern::{Match, Pattern},
+    query,
+    wrap::*,
+};
 use dashmap::DashMap;
 use derive_more::Display;
 use egglog::{
-    EGraph, SerializeConfig,
+    EGraph,
     util::{IndexMap, IndexSet},
 };
-use symbol_table::GlobalSymbol;
 use std::{collections::HashMap, path::PathBuf, sync::Mutex};
 
 #[derive(Default)]
@@ -42,12 +49,71 @@ impl TxRxVT {
         egraph.parse_and_run_program(None, s.as_str()).unwrap();
     }
     pub fn to_dot(&self, file_name: PathBuf) {
-        let egraph = self.egraph.lock().unwrap();
-        let serialized = egraph.serialize(SerializeConfig::default());
-        let dot_path = file_name.with_extension("dot");
-        serialized
-            .to_dot_file(dot_path.clone())
-            .unwrap_or_else(|_| panic!("Failed to write dot file to {dot_path:?}"));
+        export::egraph_to_dot(&self.egraph.lock().unwrap(), file_name);
+    }
+    /// write egraph in JSON format of egraph-serialize
+    pub fn to_json(&self, file_name: PathBuf) {
+        export::egraph_to_json(&self.egraph.lock().unwrap(), file_name);
+    }
+    /// write work area nodes with their version links as JSON
+    pub fn work_area_to_json(&self, file_name: PathBuf) {
+        export::work_area_to_json(self.map.iter(), file_name);
+    }
+    /// extract the best term of node's eclass by a rust side extractor, nodes of the term are created by Self
+    ///
+    /// fails if node is not in egraph or no term of finite cost is extracted
+    pub fn extract_with<N: EgglogNode + FromEgglog>(
+        &self,
+        node: &N,
+        extractor: &impl Extractor,
+    ) -> Result<N, String> {
+        let sym = node.cur_sym();
+        let egraph = SerializedEGraph::serialize(&mut self.egraph.lock().unwrap(), sym.as_str())
+            .map_err(|e| format!("failed to serialize egraph of {sym}: {e}"))?;
+        let choices = extractor.extract(&egraph);
+        let term = egraph
+            .term(&choices, egraph.roots[0])
+            .ok_or_else(|| format!("no finite cost term is extracted for {sym}"))?;
+        Ok(<N as FromEgglog>::from_egglog(&query::parse_term(&term)))
+    }
+    /// eclass of node by its current sym, the node should be committed
+    pub fn eclass_of<N: EgglogNode>(&self, node: &N) -> query::EClassId {
+        let sym = node.cur_sym();
+        query::eclass_of(&mut self.egraph.lock().unwrap(), sym.as_str())
+            .unwrap_or_else(|e| panic!("failed to get eclass of {sym}: {e}"))
+    }
+    /// whether nodes are proven equivalent by rules run so far
+    pub fn are_equal<A: EgglogNode, B: EgglogNode>(&self, a: &A, b: &B) -> bool {
+        self.eclass_of(a) == self.eclass_of(b)
+    }
+    /// check fact such as `check!(Selected::apply(()) == root)`, nodes in it should be committed
+    pub fn check(&self, fact: Fact) -> Result<(), CheckFailure> {
+        log::info!("(check {})", fact);
+        check::check(&mut self.egraph.lock().unwrap(), &fact)
+    }
+    /// at most max_size matches of pattern, nodes of bindings are created by the Tx they are read as
+    pub fn query(&self, pattern: &Pattern, max_size: Option<usize>) -> impl Iterator<Item = Match> {
+        log::info!("(query {})", pattern.facts());
+        pattern
+            .run(&mut self.egraph.lock().unwrap(), max_size)
+            .unwrap_or_else(|e| panic!("failed to query {}: {e}", pattern.facts()))
+            .into_iter()
+    }
+    /// at most k distinct terms equivalent to node ranked by annotated costs, cheapest first
+    ///
+    /// fails if node is not in egraph
+    pub fn extract_variants<N: EgglogNode + FromEgglog>(
+        &self,
+        node: &N,
+        k: usize,
+    ) -> Result<Vec<N>, String> {
+        let sym = node.cur_sym();
+        let egraph = SerializedEGraph::serialize(&mut self.egraph.lock().unwrap(), sym.as_str())
+            .map_err(|e| format!("failed to serialize egraph of {sym}: {e}"))?;
+        Ok(extract::variants(&egraph, &AnnotatedCost, egraph.roots[0], k)
+            .into_iter()
+            .map(|(_, term)| <N as FromEgglog>::from_egglog(&query::parse_term(&term)))
+            .collect())
     }
     // collect all lastest ancestors of cur_sym, without cur_sym
     pub fn collect_latest_ancestors(&self, cur_sym: Sym, index_set: &mut IndexSet<Sym>) {
@@ -400,6 +466,9 @@ impl TxCommit for TxRxVT {
         log::debug!("{}", check_point);
         self.checkpoints.lock().unwrap().push(check_point);
 
+        // all egglog code of this commit is collected into one program and sent once
+        let mut program = String::new();
+
         // process new nodes
         let mut news = self.staged_new_map.lock().unwrap();
         let mut backup_staged_new_syms = IndexSet::default();
@@ -408,11 +477,9 @@ impl TxCommit for TxRxVT {
             self.add_node(WorkAreaNode::new(new_node.clone_dyn()), false);
             backup_staged_new_syms.insert(new);
         }
-        // send egglog string to egraph
+        drop(news);
         backup_staged_new_syms.into_iter().for_each(|sym| {
-            self.send(TxCommand::StringCommand {
-                string_command: self.map.get(&sym).unwrap().egglog.to_egglog(),
-            })
+            program.push_str(&self.map.get(&sym).unwrap().egglog.to_egglog());
         });
 
         let all_staged = IndexSet::from_iter(self.staged_set_map.iter().map(|a| *a.key()));
@@ -447,10 +514,14 @@ impl TxCommit for TxRxVT {
         self.topo_sort(&created, TopoDirection::Up)
             .into_iter()
             .for_each(|sym| {
-                self.send(TxCommand::StringCommand {
-                    string_command: self.map.get(&sym).unwrap().egglog.to_egglog(),
-                })
+                program.push_str(&self.map.get(&sym).unwrap().egglog.to_egglog());
             });
+        // send egglog program to egraph
+        if !program.is_empty() {
+            self.send(TxCommand::StringCommand {
+                string_command: program,
+            });
+        }
     }
 
     fn on_stage<T: EgglogNode + ?Sized>(&self, node: &T) {
@@ -459,45 +530,30 @@ impl TxCommit for TxRxVT {
 }
 
 impl Rx for TxRxVT {
-    fn on_func_get<'a, 'b, F: EgglogFunc>(
+    fn on_func_get<'a, F: EgglogFunc>(
         &self,
-        _input: <F::Input as EgglogFuncInputs>::Ref<'a>,
-    ) -> <F::Output as EgglogFuncOutput>::Ref<'b> {
-        let mut _egraph = self.egraph.lock().unwrap();
-        // let dag = egraph.function_to_dag(EgglogFunc::FUNC_NAME.into(), max_size.unwrap_or(usize::MAX));
-        let sym :GlobalSymbol=F::FUNC_NAME.into();
-        let _f = _egraph.functions.get(&sym);
-        // match f{
-        //     Some(f) => {
-        //         let nodes = f
-        //             .nodes
-        //             .iter(true)
-        //             .take(1)
-        //             .map(|(k, v)| (ValueVec::from(k), v.clone()))
-        //             .collect::<Vec<_>>();
-        //             };
-        //     None => todo!(),
-        // };
-        panic!();
+        input: <F::Input as EgglogFuncInputs>::Ref<'a>,
+    ) -> F::Output
+    where
+        F::Output: FromEgglog,
+    {
+        let call = func_call_term::<F>(&input.as_nodes());
+        let term = query::extract(&mut self.egraph.lock().unwrap(), &call)
+            .unwrap_or_else(|e| panic!("failed to get {call}: {e}"));
+        <F::Output as FromEgglog>::from_egglog(&query::parse_term(&term))
     }
 
-    fn on_funcs_get<'a, 'b, F: EgglogFunc>(
+    fn on_funcs_get_terms(
         &self,
-        _max_size: Option<usize>,
-    ) -> Vec<(
-        <F::Input as EgglogFuncInputs>::Ref<'b>,
-        <F::Output as EgglogFuncOutput>::Ref<'b>,
-    )> {
-        todo!()
+        func_name: &'static str,
+        offset: usize,
+        max_size: Option<usize>,
+    ) -> Vec<(String, String)> {
+        query::function_rows(&mut self.egraph.lock().unwrap(), func_name, offset, max_size)
+            .unwrap_or_else(|e| panic!("failed to get rows of {func_name}: {e}"))
     }
 
     fn on_pull(&self,_node:&(impl EgglogNode + 'static)) {
         todo!()
     }
-    
-    // fn on_funcs_get<'a,'b, F: EgglogFunc>(
-    //     &self, max_size:Option<usize>) ->
-    // Vec<(<<F as EgglogFunc>::Input as EgglogFuncInputs>::Ref<'b>, <<F as EgglogFunc>::Output as EgglogFuncOutput>::Ref<'b>)> {
-    //     todo!()
-    // }
 }
@@ -1,20 +1,26 @@
-use crate::{collect_string_type_defs, wrap::*};
+use crate::{collect_string_type_defs, export, wrap::*};
 use dashmap::DashMap;
 use derive_more::Display;
 use egglog::{
-    EGraph, SerializeConfig,
+    EGraph,
     util::{IndexMap, IndexSet},
 };
-use std::{collections::HashMap, path::PathBuf, sync::Mutex};
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{Mutex, MutexGuard},
+};
 
-#[derive(Default)]
 pub struct TxVT {
-    egraph: Mutex<EGraph>,
+    /// None if egglog commands are passed to sink
+    egraph: Option<Mutex<EGraph>>,
     map: DashMap<Sym, WorkAreaNode>,
     /// used to store staged node of committed nodes (Not only the currently latest node but also nodes of old versions)
     staged_set_map: DashMap<Sym, Box<dyn EgglogNode>>,
     staged_new_map: Mutex<IndexMap<Sym, Box<dyn EgglogNode>>>,
     checkpoints: Mutex<Vec<CommitCheckPoint>>,
+    /// if set, egglog commands are passed to sink instead of being interpreted by local egraph
+    sink: Option<Box<dyn Fn(TxCommand) + Send + Sync>>,
 }
 
 #[derive(Debug, Display)]
@@ -37,16 +43,19 @@ pub enum TopoDirection {
 impl TxVT {
     pub fn interpret_string(&self, s: String) {
         log::info!("{}", s);
-        let mut egraph = self.egraph.lock().unwrap();
+        let mut egraph = self.egraph();
         egraph.parse_and_run_program(None, s.as_str()).unwrap();
     }
     pub fn to_dot(&self, file_name: PathBuf) {
-        let egraph = self.egraph.lock().unwrap();
-        let serialized = egraph.serialize(SerializeConfig::default());
-        let dot_path = file_name.with_extension("dot");
-        serialized
-            .to_dot_file(dot_path.clone())
-            .unwrap_or_else(|_| panic!("Failed to write dot file to {dot_path:?}"));
+        export::egraph_to_dot(&self.egraph(), file_name);
+    }
+    /// write egraph in JSON format of egraph-serialize
+    pub fn to_json(&self, file_name: PathBuf) {
+        export::egraph_to_json(&self.egraph(), file_name);
+    }
+    /// write work area nodes with their version links as JSON
+    pub fn work_area_to_json(&self, file_name: PathBuf) {
+        export::work_area_to_json(self.map.iter(), file_name);
     }
     // collect all lastest ancestors of cur_sym, without cur_sym
     pub fn collect_latest_ancestors(&self, cur_sym: Sym, index_set: &mut IndexSet<Sym>) {
@@ -150,20 +159,43 @@ impl TxVT {
             }
         })
     }
-    pub fn new_with_type_defs(type_defs: String) -> Self {
+    fn with_backend(
+        egraph: Option<EGraph>,
+        sink: Option<Box<dyn Fn(TxCommand) + Send + Sync>>,
+    ) -> Self {
         Self {
-            egraph: Mutex::new({
-                let mut e = EGraph::default();
-                log::info!("{}", type_defs);
-                e.parse_and_run_program(None, type_defs.as_ref()).unwrap();
-                e
-            }),
-            ..Self::default()
+            egraph: egraph.map(Mutex::new),
+            map: DashMap::default(),
+            staged_set_map: DashMap::default(),
+            staged_new_map: Mutex::default(),
+            checkpoints: Mutex::default(),
+            sink,
         }
     }
+    fn egraph(&self) -> MutexGuard<'_, EGraph> {
+        self.egraph
+            .as_ref()
+            .expect("egraph of Tx is owned by the receiver of its sink")
+            .lock()
+            .unwrap()
+    }
+    pub fn new_with_type_defs(type_defs: String) -> Self {
+        let mut egraph = EGraph::default();
+        log::info!("{}", type_defs);
+        egraph
+            .parse_and_run_program(None, type_defs.as_ref())
+            .unwrap();
+        Self::with_backend(Some(egraph), None)
+    }
     pub fn new() -> Self {
         Self::new_with_type_defs(collect_string_type_defs())
     }
+    /// create a Tx whose egglog commands are all passed to `sink`
+    ///
+    /// no local egraph is created, the receiver of sink is responsible for type definitions
+    pub fn new_with_sink(sink: impl Fn(TxCommand) + Send + Sync + 'static) -> Self {
+        Self::with_backend(None, Some(Box::new(sink)))
+    }
     fn add_node(&self, mut node: WorkAreaNode, auto_latest: bool) {
         let sym = node.cur_sym();
         for node in node.succs_mut() {
@@ -289,6 +321,12 @@ impl TxVT {
     }
 }
 
+impl Default for TxVT {
+    fn default() -> Self {
+        Self::with_backend(Some(EGraph::default()), None)
+    }
+}
+
 unsafe impl Send for TxVT {}
 unsafe impl Sync for TxVT {}
 impl VersionCtl for TxVT {
@@ -339,6 +377,9 @@ impl VersionCtl for TxVT {
 // MARK: Receiver
 impl Tx for TxVT {
     fn send(&self, received: TxCommand) {
+        if let Some(sink) = &self.sink {
+            return sink(received);
+        }
         match received {
             TxCommand::StringCommand { string_command } => {
                 self.interpret_string(string_command);
@@ -397,6 +438,9 @@ impl TxCommit for TxVT {
         log::debug!("{}", check_point);
         self.checkpoints.lock().unwrap().push(check_point);
 
+        // all egglog code of this commit is collected into one program and sent once
+        let mut program = String::new();
+
         // process new nodes
         let mut news = self.staged_new_map.lock().unwrap();
         let mut backup_staged_new_syms = IndexSet::default();
@@ -405,11 +449,9 @@ impl TxCommit for TxVT {
             self.add_node(WorkAreaNode::new(new_node.clone_dyn()), false);
             backup_staged_new_syms.insert(new);
         }
-        // send egglog string to egraph
+        drop(news);
         backup_staged_new_syms.into_iter().for_each(|sym| {
-            self.send(TxCommand::StringCommand {
-                string_command: self.map.get(&sym).unwrap().egglog.to_egglog(),
-            })
+            program.push_str(&self.map.get(&sym).unwrap().egglog.to_egglog());
         });
 
         let all_staged = IndexSet::from_iter(self.staged_set_map.iter().map(|a| *a.key()));
@@ -444,10 +486,14 @@ impl TxCommit for TxVT {
         self.topo_sort(&created, TopoDirection::Up)
             .into_iter()
             .for_each(|sym| {
-                self.send(TxCommand::StringCommand {
-                    string_command: self.map.get(&sym).unwrap().egglog.to_egglog(),
-                })
+                program.push_str(&self.map.get(&sym).unwrap().egglog.to_egglog());
+            });
+        // send egglog program to egraph
+        if !program.is_empty() {
+            self.send(TxCommand::StringCommand {
+                string_command: program,
             });
+        }
     }
 
     fn on_stage<T: EgglogNode + ?Sized>(&self, node: &T) {
@@ -1,8 +1,11 @@
 use derive_more::{Debug, Deref, DerefMut, IntoIterator};
-use egglog::ast::NCommand;
+use egglog::ast::{GenericExpr, Literal, NCommand};
 use impl_trait_for_tuples::impl_for_tuples;
 use smallvec::SmallVec;
-use std::{borrow::Borrow, fmt, hash::Hash, marker::PhantomData, sync::atomic::AtomicU32};
+use std::{
+    borrow::Borrow, collections::BTreeMap, fmt, hash::Hash, marker::PhantomData,
+    sync::atomic::AtomicU32,
+};
 use symbol_table::GlobalSymbol;
 
 #[derive(Debug)]
@@ -23,17 +26,47 @@ pub trait Tx: 'static {
     );
 }
 pub trait Rx: 'static {
-    fn on_func_get<'a, 'b, F: EgglogFunc>(
+    /// read output of function F from egraph, nodes of the extracted term are created by the Tx
+    fn on_func_get<'a, F: EgglogFunc>(
         &self,
         input: <F::Input as EgglogFuncInputs>::Ref<'a>,
-    ) -> <F::Output as EgglogFuncOutput>::Ref<'b>;
-    fn on_funcs_get<'a, 'b, F: EgglogFunc>(
+    ) -> F::Output
+    where
+        F::Output: FromEgglog;
+    /// read at most max_size rows of function F from egraph
+    fn on_funcs_get<F: EgglogFunc>(&self, max_size: Option<usize>) -> Vec<(F::Input, F::Output)>
+    where
+        F::Input: FromEgglogArgs,
+        F::Output: FromEgglog,
+    {
+        self.on_funcs_get_page::<F>(0, max_size)
+    }
+    /// read at most max_size rows of function F after skipping the first offset rows of its table
+    ///
+    /// every page reads the table again, so pages of a table changed in between may skip or
+    /// repeat rows
+    fn on_funcs_get_page<F: EgglogFunc>(
         &self,
+        offset: usize,
         max_size: Option<usize>,
-    ) -> Vec<(
-        <F::Input as EgglogFuncInputs>::Ref<'b>,
-        <F::Output as EgglogFuncOutput>::Ref<'b>,
-    )>;
+    ) -> Vec<(F::Input, F::Output)>
+    where
+        F::Input: FromEgglogArgs,
+        F::Output: FromEgglog,
+    {
+        self.on_funcs_get_terms(F::FUNC_NAME, offset, max_size)
+            .iter()
+            .map(|(call, output)| typed_row::<F>(call, output))
+            .collect()
+    }
+    /// read at most max_size rows of function after skipping the first offset rows of its table,
+    /// each row is (call term, output term) such as `("(F a b)", "(End)")`
+    fn on_funcs_get_terms(
+        &self,
+        func_name: &'static str,
+        offset: usize,
+        max_size: Option<usize>,
+    ) -> Vec<(String, String)>;
     fn on_pull(&self,node:&(impl EgglogNode + 'static));
 }
 
@@ -54,15 +87,18 @@ pub trait TxSgl: 'static + Sized + SingletonGetter {
 }
 pub trait RxSgl: 'static + Sized + SingletonGetter {
     // delegate all functions from Rx
-    fn on_func_get<'a, 'b, F: EgglogFunc>(
-        input: <F::Input as EgglogFuncInputs>::Ref<'a>,
-    ) -> <F::Output as EgglogFuncOutput>::Ref<'b>;
-    fn on_funcs_get<'a, 'b, F: EgglogFunc>(
-        max_size: Option<usize>,
-    ) -> Vec<(
-        <F::Input as EgglogFuncInputs>::Ref<'b>,
-        <F::Output as EgglogFuncOutput>::Ref<'b>,
-    )>;
+    fn on_func_get<'a, F: EgglogFunc>(input: <F::Input as EgglogFuncInputs>::Ref<'a>) -> F::Output
+    where
+        F::Output: FromEgglog;
+    fn on_funcs_get<F: EgglogFunc>(max_size: Option<usize>) -> Vec<(F::Input, F::Output)>
+    where
+        F::Input: FromEgglogArgs,
+        F::Output: FromEgglog;
+    fn on_funcs_get_page<F: EgglogFunc>(offset: usize, max_size: Option<usize>) -> Vec<(F::Input, F::Output)>
+    where
+        F::Input: FromEgglogArgs,
+        F::Output: FromEgglog;
+    fn on_funcs_get_terms(func_name: &'static str, offset: usize, max_size: Option<usize>) -> Vec<(String, String)>;
     fn on_pull(node:&(impl EgglogNode + 'static) );
 }
 
@@ -85,20 +121,30 @@ impl<T: Tx + 'static, S: SingletonGetter<RetTy = T> + 'static> TxSgl for S {
     }
 }
 impl<R: Rx + 'static, S: SingletonGetter<RetTy = R> + 'static> RxSgl for S {
-    fn on_func_get<'a, 'b, F: EgglogFunc>(
-        input: <F::Input as EgglogFuncInputs>::Ref<'a>,
-    ) -> <F::Output as EgglogFuncOutput>::Ref<'b> {
+    fn on_func_get<'a, F: EgglogFunc>(input: <F::Input as EgglogFuncInputs>::Ref<'a>) -> F::Output
+    where
+        F::Output: FromEgglog,
+    {
         Self::sgl().on_func_get::<F>(input)
     }
 
-    fn on_funcs_get<'a, 'b, F: EgglogFunc>(
-        max_size: Option<usize>,
-    ) -> Vec<(
-        <F::Input as EgglogFuncInputs>::Ref<'b>,
-        <F::Output as EgglogFuncOutput>::Ref<'b>,
-    )> {
+    fn on_funcs_get<F: EgglogFunc>(max_size: Option<usize>) -> Vec<(F::Input, F::Output)>
+    where
+        F::Input: FromEgglogArgs,
+        F::Output: FromEgglog,
+    {
         Self::sgl().on_funcs_get::<F>(max_size)
     }
+    fn on_funcs_get_page<F: EgglogFunc>(offset: usize, max_size: Option<usize>) -> Vec<(F::Input, F::Output)>
+    where
+        F::Input: FromEgglogArgs,
+        F::Output: FromEgglog,
+    {
+        Self::sgl().on_funcs_get_page::<F>(offset, max_size)
+    }
+    fn on_funcs_get_terms(func_name: &'static str, offset: usize, max_size: Option<usize>) -> Vec<(String, String)> {
+        Self::sgl().on_funcs_get_terms(func_name, offset, max_size)
+    }
     fn on_pull(node:&(impl EgglogNode + 'static) ) {
         Self::sgl().on_pull(node)
     }
@@ -147,18 +193,127 @@ impl<Ret: Tx + VersionCtl + 'static, S: SingletonGetter<RetTy = Ret>> VersionCtl
     }
 }
 
-pub trait EgglogTy {
+pub trait EgglogTy: Clone + 'static {
     const TY_NAME: &'static str;
     const TY_NAME_LOWER: &'static str;
+    /// sort definition as declared, sorts used at runtime are rendered by `collect_sort_defs`
     const SORT_DEF: TySort;
+    /// egglog sort, instance of generic type is mangled with sorts of its type args such as `ListCtl`
+    fn sort_name() -> String {
+        Self::TY_NAME.to_owned()
+    }
+    /// render definition of its sort, including option sorts and instances it depends on
+    fn collect_sort_defs(defs: &mut SortDefs);
+}
+/// node type of EgglogTy for Tx T, so that generic types could take nodes of their type params
+pub trait EgglogTyNode<T: TxSgl>: EgglogTy {
+    type Node: EgglogNode + FromEgglog + AsRef<Self::Node> + Clone + 'static;
 }
 pub trait UpdateCounter<T: EgglogTy> {
     fn inc_counter(&mut self, counter: &mut TyCounter<T>) -> Sym<T>;
 }
 pub struct TySort(pub &'static str);
-pub struct FuncSort(pub &'static str);
+/// type used somewhere, its sort definitions are collected at startup
+pub struct SortCollector(pub fn(&mut SortDefs));
+/// function named `.0` defined by `.1`, its definition is rendered at startup by `.2`
+pub struct FuncSort(pub &'static str, pub &'static str, pub fn() -> String);
 pub struct RelationSort(pub &'static str);
 
+/// sort definitions rendered at startup keyed by sort name, each records the rust item defining it
+/// so that names conflicting in the single `datatype*` block could be reported
+#[derive(Default)]
+pub struct SortDefs {
+    defs: BTreeMap<String, SortDef>,
+    /// prefix of syms bound by `let`, and the rust item whose nodes take it
+    sym_prefixes: BTreeMap<String, String>,
+    conflicts: Vec<String>,
+}
+struct SortDef {
+    defined_by: String,
+    ctors: Vec<String>,
+    def: String,
+}
+impl SortDefs {
+    /// reserve sort before rendering it, return false if it's already collected so recursive instances stop
+    pub fn reserve(&mut self, sort: &str, defined_by: &str) -> bool {
+        match self.defs.get(sort) {
+            Some(collected) => {
+                if collected.defined_by != defined_by {
+                    self.conflicts.push(format!(
+                        "sort `{}` is defined by both `{}` and `{}`",
+                        sort, collected.defined_by, defined_by
+                    ));
+                }
+                false
+            }
+            None => {
+                self.defs.insert(
+                    sort.to_owned(),
+                    SortDef {
+                        defined_by: defined_by.to_owned(),
+                        ctors: vec![],
+                        def: String::new(),
+                    },
+                );
+                true
+            }
+        }
+    }
+    /// reserve prefix of node syms, nodes of different types sharing it would overwrite each other
+    pub fn reserve_sym_prefix(&mut self, sym_prefix: &str, defined_by: &str) {
+        match self.sym_prefixes.get(sym_prefix) {
+            Some(prev) if prev != defined_by => self.conflicts.push(format!(
+                "sym prefix `{}` is taken by both `{}` and `{}`",
+                sym_prefix, prev, defined_by
+            )),
+            Some(_) => {}
+            None => {
+                self.sym_prefixes
+                    .insert(sym_prefix.to_owned(), defined_by.to_owned());
+            }
+        }
+    }
+    /// fill definition of reserved sort
+    pub fn insert(&mut self, sort: &str, ctors: Vec<String>, def: String) {
+        let collected = self
+            .defs
+            .get_mut(sort)
+            .expect("sort should be reserved before inserted");
+        collected.ctors = ctors;
+        collected.def = def;
+    }
+    /// option datatype of field, such as `(OptionHitBox (SomeHitBox HitBox) (NoneHitBox))`
+    pub fn insert_option(&mut self, option: &str, some: &str, none: &str, inner: String) {
+        if self.reserve(option, &format!("Option<{}>", inner)) {
+            let def = format!("({} ({} {}) ({}))", option, some, inner, none);
+            self.insert(option, vec![some.to_owned(), none.to_owned()], def);
+        }
+    }
+    pub fn defs(&self) -> impl Iterator<Item = &str> {
+        self.defs.values().map(|collected| collected.def.as_str())
+    }
+    /// conflicting sorts and sym prefixes, and constructors or functions sharing a name
+    pub fn conflicts<'a>(&'a self, funcs: impl IntoIterator<Item = (&'a str, &'a str)>) -> Vec<String> {
+        let mut conflicts = self.conflicts.clone();
+        let mut callables = BTreeMap::<&str, &str>::new();
+        let ctors = self.defs.values().flat_map(|collected| {
+            collected
+                .ctors
+                .iter()
+                .map(|ctor| (ctor.as_str(), collected.defined_by.as_str()))
+        });
+        for (name, defined_by) in ctors.chain(funcs) {
+            if let Some(prev) = callables.insert(name, defined_by) {
+                conflicts.push(format!(
+                    "constructor or function `{}` is defined by both `{}` and `{}`",
+                    name, prev, defined_by
+                ));
+            }
+        }
+        conflicts
+    }
+}
+
 impl<T> Sym<T> {
     pub fn erase(&self) -> Sym<()> {
         // safety note: type erasure
@@ -179,6 +334,301 @@ pub trait ToEgglog {
     fn to_egglog(&self) -> String;
 }
 
+/// egglog term read out of egraph
+pub type EgglogExpr = egglog::ast::Expr;
+
+/// trait of building value from egglog term, so that Rx could return typed nodes
+///
+/// for nodes, the nodes of all subterms are created by calling new fns of their types
+pub trait FromEgglog: Sized {
+    fn from_egglog(expr: &EgglogExpr) -> Self;
+}
+
+/// trait of building input tuple of egglog function from args of a call term
+pub trait FromEgglogArgs: Sized {
+    fn from_egglog_args(args: &[EgglogExpr]) -> Self;
+}
+
+/// rust primitive types which are stored in egraph as egglog literals
+pub trait EgglogPrim: FromEgglog {
+    /// egglog sort storing the type
+    const SORT: &'static str;
+    /// render self as egglog literal of its sort
+    fn to_egglog_lit(&self) -> String;
+}
+
+/// types a query variable could be bound to, generated nodes and primitives
+pub trait EgglogBinding: FromEgglog {
+    fn sort_name() -> String;
+}
+
+/// integers are stored by egglog i64 sort, u64 and usize are left out as they could exceed it
+macro_rules! impl_egglog_prim_int {
+    ($($ty:ty),*) => {$(
+        impl EgglogPrim for $ty {
+            const SORT: &'static str = "i64";
+            #[allow(clippy::useless_conversion)]
+            fn to_egglog_lit(&self) -> String {
+                i64::from(*self).to_string()
+            }
+        }
+        impl FromEgglog for $ty {
+            #[allow(clippy::useless_conversion)]
+            fn from_egglog(expr: &EgglogExpr) -> Self {
+                match expr {
+                    GenericExpr::Lit(_, Literal::Int(i)) => <$ty>::try_from(*i)
+                        .unwrap_or_else(|_| panic!("{} overflows {}", i, stringify!($ty))),
+                    _ => panic!("expect {} literal, found {expr}", stringify!($ty)),
+                }
+            }
+        }
+    )*};
+}
+impl_egglog_prim_int!(i8, i16, i32, i64, u8, u16, u32);
+
+/// rendered losslessly, NaN and infinities use the spelling of egglog parser
+impl EgglogPrim for f64 {
+    const SORT: &'static str = "f64";
+    fn to_egglog_lit(&self) -> String {
+        if self.is_nan() {
+            "NaN".to_owned()
+        } else if self.is_infinite() {
+            if *self > 0.0 { "inf" } else { "-inf" }.to_owned()
+        } else {
+            // Display never uses exponent and prints the shortest repr which round trips,
+            // integral values need a dot or they would be parsed as i64
+            let s = self.to_string();
+            if s.contains('.') { s } else { s + ".0" }
+        }
+    }
+}
+impl FromEgglog for f64 {
+    fn from_egglog(expr: &EgglogExpr) -> Self {
+        match expr {
+            GenericExpr::Lit(_, Literal::Float(f)) => f.into_inner(),
+            _ => panic!("expect f64 literal, found {expr}"),
+        }
+    }
+}
+/// f32 is widened to egglog f64 sort
+impl EgglogPrim for f32 {
+    const SORT: &'static str = "f64";
+    fn to_egglog_lit(&self) -> String {
+        (*self as f64).to_egglog_lit()
+    }
+}
+impl FromEgglog for f32 {
+    fn from_egglog(expr: &EgglogExpr) -> Self {
+        f64::from_egglog(expr) as f32
+    }
+}
+impl EgglogPrim for bool {
+    const SORT: &'static str = "bool";
+    fn to_egglog_lit(&self) -> String {
+        self.to_string()
+    }
+}
+impl FromEgglog for bool {
+    fn from_egglog(expr: &EgglogExpr) -> Self {
+        match expr {
+            GenericExpr::Lit(_, Literal::Bool(b)) => *b,
+            _ => panic!("expect bool literal, found {expr}"),
+        }
+    }
+}
+/// unit is stored by egglog Unit sort
+impl EgglogPrim for () {
+    const SORT: &'static str = "Unit";
+    fn to_egglog_lit(&self) -> String {
+        "()".to_owned()
+    }
+}
+impl FromEgglog for () {
+    fn from_egglog(expr: &EgglogExpr) -> Self {
+        match expr {
+            GenericExpr::Lit(_, Literal::Unit) => (),
+            _ => panic!("expect Unit literal, found {expr}"),
+        }
+    }
+}
+/// quoted, with backslashes and quotes escaped
+impl EgglogPrim for String {
+    const SORT: &'static str = "String";
+    fn to_egglog_lit(&self) -> String {
+        let mut lit = String::with_capacity(self.len() + 2);
+        lit.push('"');
+        for c in self.chars() {
+            if c == '"' || c == '\\' {
+                lit.push('\\');
+            }
+            lit.push(c);
+        }
+        lit.push('"');
+        lit
+    }
+}
+impl FromEgglog for String {
+    fn from_egglog(expr: &EgglogExpr) -> Self {
+        match expr {
+            GenericExpr::Lit(_, Literal::String(s)) => s.as_str().to_owned(),
+            _ => panic!("expect String literal, found {expr}"),
+        }
+    }
+}
+
+macro_rules! impl_egglog_binding_prim {
+    ($($ty:ty),*) => {$(
+        impl EgglogBinding for $ty {
+            fn sort_name() -> String {
+                <$ty as EgglogPrim>::SORT.to_owned()
+            }
+        }
+    )*};
+}
+impl_egglog_binding_prim!(i8, i16, i32, i64, u8, u16, u32, f64, f32, bool, (), String);
+
+/// split egglog term `(head arg0 arg1 ..)` into head and args
+pub fn expr_to_call(expr: &EgglogExpr) -> (&'static str, &[EgglogExpr]) {
+    match expr {
+        GenericExpr::Call(_, head, args) => (head.as_str(), args),
+        _ => panic!("expect egglog call term, found {expr}"),
+    }
+}
+
+/// elements of egglog Vec or Set term, such as `(vec-of a b)` or `(set-empty)`
+pub fn container_elements<'a>(expr: &'a EgglogExpr, of: &str, empty: &str) -> &'a [EgglogExpr] {
+    let (head, args) = expr_to_call(expr);
+    if head != of && head != empty {
+        panic!("expect {of} term, found {expr}");
+    }
+    args
+}
+
+/// entries of egglog Map term `(map-insert (map-insert (map-empty) k0 v0) k1 v1)`, in insertion order
+pub fn map_entries(mut expr: &EgglogExpr) -> Vec<(&EgglogExpr, &EgglogExpr)> {
+    let mut entries = Vec::new();
+    loop {
+        match expr_to_call(expr) {
+            ("map-insert", [map, key, value]) => {
+                entries.push((key, value));
+                expr = map;
+            }
+            ("map-empty", []) => break,
+            _ => panic!("expect map term, found {expr}"),
+        }
+    }
+    entries.reverse();
+    entries
+}
+
+/// egglog term of calling function F with input nodes, such as `(F a b)`
+pub fn func_call_term<F: EgglogFunc>(input_nodes: &[&dyn EgglogNode]) -> String {
+    input_nodes
+        .iter()
+        .fold(format!("({}", F::FUNC_NAME), |s, node| {
+            s + " " + node.cur_sym().as_str()
+        })
+        + ")"
+}
+
+/// typed row of function F from its call term and output term, nodes of both are created by the Tx
+/// they are read as
+pub fn typed_row<F: EgglogFunc>(call: &str, output: &str) -> (F::Input, F::Output)
+where
+    F::Input: FromEgglogArgs,
+    F::Output: FromEgglog,
+{
+    let call = crate::query::parse_term(call);
+    let (_, args) = expr_to_call(&call);
+    (
+        <F::Input as FromEgglogArgs>::from_egglog_args(args),
+        <F::Output as FromEgglog>::from_egglog(&crate::query::parse_term(output)),
+    )
+}
+
+/// number of rows [`FuncRows`] reads at once by default
+pub const FUNC_ROWS_PAGE_SIZE: usize = 256;
+
+/// all rows of function F read from egraph of R, page by page
+///
+/// a page of terms is read when rows of the previous one are used up, and rows are typed as they
+/// are iterated, so that neither terms nor nodes of huge tables are held at once. pages are read
+/// by offset like [`Rx::on_funcs_get_page`], so rows set while iterating may be skipped or repeated
+pub struct FuncRows<F: EgglogFunc, R: RxSgl> {
+    page: std::vec::IntoIter<(String, String)>,
+    page_size: usize,
+    offset: usize,
+    /// a short page is the last one
+    exhausted: bool,
+    _p: PhantomData<(F, R)>,
+}
+impl<F: EgglogFunc, R: RxSgl> FuncRows<F, R> {
+    /// read at most page_size rows at once, page_size should be positive
+    pub fn with_page_size(page_size: usize) -> Self {
+        assert!(page_size > 0, "page size of FuncRows should be positive");
+        FuncRows {
+            page: Vec::new().into_iter(),
+            page_size,
+            offset: 0,
+            exhausted: false,
+            _p: PhantomData,
+        }
+    }
+}
+impl<F: EgglogFunc, R: RxSgl> Default for FuncRows<F, R> {
+    fn default() -> Self {
+        Self::with_page_size(FUNC_ROWS_PAGE_SIZE)
+    }
+}
+impl<F: EgglogFunc, R: RxSgl> Iterator for FuncRows<F, R>
+where
+    F::Input: FromEgglogArgs,
+    F::Output: FromEgglog,
+{
+    type Item = (F::Input, F::Output);
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((call, output)) = self.page.next() {
+                return Some(typed_row::<F>(&call, &output));
+            }
+            if self.exhausted {
+                return None;
+            }
+            let page = R::on_funcs_get_terms(F::FUNC_NAME, self.offset, Some(self.page_size));
+            self.offset += page.len();
+            self.exhausted = page.len() < self.page_size;
+            self.page = page.into_iter();
+        }
+    }
+}
+
+/// egglog expression standing for an eclass, terms of the same Sort could be compared in facts
+pub trait EgglogTerm {
+    /// node type of the eclass, such as `Expr<T,()>`
+    type Sort;
+    fn term(&self) -> String;
+}
+
+/// call of function F as a term, such as `(F a b)` created by `F::apply((&a, &b))`
+pub struct FuncApp<F: EgglogFunc> {
+    term: String,
+    _p: PhantomData<F>,
+}
+impl<F: EgglogFunc> FuncApp<F> {
+    pub fn new(input_nodes: &[&dyn EgglogNode]) -> Self {
+        FuncApp {
+            term: func_call_term::<F>(input_nodes),
+            _p: PhantomData,
+        }
+    }
+}
+impl<F: EgglogFunc> EgglogTerm for FuncApp<F> {
+    type Sort = F::Output;
+    fn term(&self) -> String {
+        self.term.clone()
+    }
+}
+
 /// version control triat
 /// which should be implemented by Node
 pub trait LocateVersion {
@@ -197,10 +647,23 @@ pub trait EgglogNode: ToEgglog {
     fn cur_sym_mut(&mut self) -> &mut Sym;
 
     fn clone_dyn(&self) -> Box<dyn EgglogNode>;
+    /// egglog sort of the node, such as `ListCtl` for an instance of generic type
+    ///
+    /// generated by `#[egglog_ty]`, empty for nodes which don't tell it
+    fn sort_name(&self) -> String {
+        String::new()
+    }
+    /// constructor of the node and its fields rendered as literals or syms, such as
+    /// `("Value", ["2", "cons1"])`
+    ///
+    /// generated by `#[egglog_ty]`, None for nodes which don't tell them
+    fn ctor_fields(&self) -> Option<(String, Vec<String>)> {
+        None
+    }
 }
 
 // collect all sorts into inventory, so that we could send the definitions of types.
-inventory::collect!(TySort);
+inventory::collect!(SortCollector);
 inventory::collect!(FuncSort);
 inventory::collect!(RelationSort);
 
@@ -311,6 +774,35 @@ impl<T: EgglogTy> TyCounter<T> {
     }
 }
 
+/// count the number of nodes of all instances of generic EgglogTy, which share the sym prefix
+pub struct GenericTyCounter {
+    counter: AtomicU32,
+    ty_name_lower: &'static str,
+}
+impl GenericTyCounter {
+    pub const fn new(ty_name_lower: &'static str) -> Self {
+        GenericTyCounter {
+            counter: AtomicU32::new(0),
+            ty_name_lower,
+        }
+    }
+    // get next symbol of instance T
+    pub fn next_sym<T: EgglogTy>(&self) -> Sym<T> {
+        Sym {
+            inner: format!("{}{}", self.ty_name_lower, self.inc()).into(),
+            p: PhantomData::<T>,
+        }
+    }
+    pub fn get_counter(&self) -> u32 {
+        self.counter.load(std::sync::atomic::Ordering::Acquire)
+    }
+    /// counter increment atomically
+    pub fn inc(&self) -> u32 {
+        self.counter
+            .fetch_add(1, std::sync::atomic::Ordering::AcqRel)
+    }
+}
+
 impl EgglogEnumVariantTy for () {
     const TY_NAME: &'static str = "Unknown";
 }
@@ -548,4 +1040,13 @@ impl EgglogFuncInputsRef for TupleRef {
             #(self.TupleRef.as_node()),*
         )])
    }
-}
\ No newline at end of file
+}
+#[impl_for_tuples(0, 8)]
+#[tuple_types_custom_trait_bound(FromEgglog)]
+impl FromEgglogArgs for Tuple {
+    #[allow(unused_mut, unused_variables)]
+    fn from_egglog_args(args: &[EgglogExpr]) -> Self {
+        let mut args = args.iter();
+        (for_tuples!( #( Tuple::from_egglog(args.next().expect("too few args of egglog call")), )* ))
+    }
+}
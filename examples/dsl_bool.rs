@@ -0,0 +1,27 @@
+use egglog_macros::egglog_ty;
+use egglog_wrapper::basic_tx_vt;
+
+#[egglog_ty]
+enum Toggle {
+    On { visible: bool },
+    Marker { tick: () },
+}
+
+#[egglog_ty]
+enum Panel {
+    Item { toggle: Toggle, enabled: bool },
+}
+
+fn main() {
+    let mut toggle = Toggle::<MyTx>::new_on(true);
+    let marker = Toggle::<MyTx>::new_marker(());
+    let panel = Panel::new_item(&toggle, false);
+    println!("{}", toggle.to_egglog());
+    println!("{}", marker.to_egglog());
+    panel.commit();
+    toggle.set_visible(false).stage();
+    panel.commit();
+    MyTx::sgl().to_dot("egraph.dot".into());
+}
+
+basic_tx_vt!(MyTx);
@@ -0,0 +1,36 @@
+use egglog_macros::{egglog_func, egglog_ty};
+use egglog_wrapper::{basic_tx_rx_vt, check};
+
+#[egglog_ty]
+enum Expr {
+    Num { n: i64 },
+    Var { name: String },
+    Mul { l: Box<Expr>, r: Box<Expr> },
+    Shl { l: Box<Expr>, bits: i64 },
+}
+
+#[egglog_func(output=Expr)]
+struct Optimized {}
+
+fn main() {
+    let x = Expr::<MyTx>::new_var("x".to_owned());
+    let two = Expr::new_num(2);
+    let double = Expr::new_mul(&x, &two);
+    let shift = Expr::new_shl(&x, 1);
+    double.commit();
+    shift.commit();
+    Optimized::<MyTx>::set((), &double);
+
+    // not proven before rules are run, the failure tells what both sides are
+    match MyTx::sgl().check(check!(Optimized::apply(()) == shift)) {
+        Ok(()) => println!("proven without rules"),
+        Err(failure) => println!("{failure}"),
+    }
+    MyTx::sgl().interpret_string("(rewrite (Mul a (Num 2)) (Shl a 1)) (run 1)".to_owned());
+    MyTx::sgl()
+        .check(check!(Optimized::apply(()) == shift))
+        .unwrap();
+    MyTx::sgl().check(check!(Optimized::<MyTx>::apply(()))).unwrap();
+}
+
+basic_tx_rx_vt!(MyTx);
@@ -0,0 +1,52 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use egglog_macros::{egglog_func, egglog_ty};
+use egglog_wrapper::basic_tx_rx_vt;
+
+#[egglog_ty]
+enum Layer {
+    Named { name: String, depth: i64 },
+}
+
+#[egglog_ty]
+struct LayerSet {
+    layers: HashSet<Layer>,
+}
+
+#[egglog_ty]
+struct Tags {
+    tags: BTreeSet<String>,
+}
+
+#[egglog_ty]
+struct LayerByDepth {
+    layers: BTreeMap<i64, Layer>,
+}
+
+#[egglog_ty]
+enum Scene {
+    Scene { layers: LayerSet, tags: Tags, by_depth: LayerByDepth },
+}
+
+#[egglog_func(output=Scene)]
+struct Current {}
+
+fn main() {
+    let mut ground = Layer::<MyTx>::new_named("ground".to_owned(), 0);
+    let sky = Layer::<MyTx>::new_named("sky".to_owned(), 10);
+    let layer_set = LayerSet::new(vec![&ground, &sky]);
+    let tags = Tags::new(vec!["outdoor".to_owned(), "day".to_owned()]);
+    let by_depth = LayerByDepth::new(vec![(0, &ground as &dyn AsRef<_>), (10, &sky)]);
+    let scene = Scene::new_scene(&layer_set, &tags, &by_depth);
+    println!("{}", layer_set.to_egglog());
+    println!("{}", by_depth.to_egglog());
+    scene.commit();
+
+    // elements of containers follow new versions of their nodes
+    ground.set_depth(-1).stage();
+    scene.commit();
+    Current::<MyTx>::set((), &scene);
+    println!("{}", Current::<MyTx>::get(()).to_egglog());
+}
+
+basic_tx_rx_vt!(MyTx);
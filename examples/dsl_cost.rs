@@ -0,0 +1,33 @@
+use egglog_macros::{egglog_func, egglog_ty};
+use egglog_wrapper::basic_tx_rx_vt;
+
+#[egglog_ty]
+enum Expr {
+    Num { n: i64 },
+    Var { name: String },
+    // multiplication is expensive, so the shift it's rewritten into is extracted
+    #[egglog(cost = 8)]
+    Mul { l: Box<Expr>, r: Box<Expr> },
+    Shl { l: Box<Expr>, bits: i64 },
+}
+
+#[egglog_func(output=Expr)]
+struct Best {}
+
+fn main() {
+    let x = Expr::<MyTx>::new_var("x".to_owned());
+    let two = Expr::new_num(2);
+    let double = Expr::new_mul(&x, &two);
+    double.commit();
+    Best::<MyTx>::set((), &double);
+
+    MyTx::sgl().interpret_string("(rewrite (Mul a (Num 2)) (Shl a 1)) (run 1)".to_owned());
+    // the extracted term is the shift, as Mul costs more than Shl and Num together
+    println!("{}", Best::<MyTx>::get(()).to_egglog());
+    // equivalent alternatives, cheapest first
+    for alternative in MyTx::sgl().extract_variants(double.as_ref(), 3).unwrap() {
+        println!("{}", alternative.to_egglog());
+    }
+}
+
+basic_tx_rx_vt!(MyTx);
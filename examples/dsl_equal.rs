@@ -0,0 +1,23 @@
+use egglog_macros::egglog_ty;
+use egglog_wrapper::basic_tx_rx_vt;
+
+#[egglog_ty]
+enum Ctl {
+    Act { name: String },
+    Seq { inner: Box<Ctl> },
+    Para { inner: Box<Ctl> },
+}
+
+fn main() {
+    let x = Ctl::<MyTx>::new_act("fade".to_owned());
+    let para = Ctl::new_para(&Ctl::new_seq(&x));
+    para.commit();
+    assert!(!MyTx::sgl().are_equal(&para, &x));
+
+    MyTx::sgl().interpret_string("(rewrite (Para (Seq x)) x) (run 1)".to_owned());
+    // simplify(Para[Seq[x]]) == x
+    assert!(MyTx::sgl().are_equal(&para, &x));
+    println!("{:?}", MyTx::sgl().eclass_of(&para));
+}
+
+basic_tx_rx_vt!(MyTx);
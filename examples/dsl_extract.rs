@@ -0,0 +1,35 @@
+use egglog_macros::egglog_ty;
+use egglog_wrapper::{
+    basic_tx_rx_vt,
+    extract::{GreedyDagExtractor, TyCosts},
+};
+
+#[egglog_ty]
+enum Duration {
+    Secs { seconds: f64 },
+}
+
+#[egglog_ty]
+enum Anim {
+    Wait { d: Duration },
+    Seq { first: Box<Anim>, then: Box<Anim> },
+}
+
+fn main() {
+    let one = Duration::<MyTx>::new_secs(1.0);
+    let two = Duration::new_secs(2.0);
+    let waits = Anim::new_seq(&Anim::new_wait(&one), &Anim::new_wait(&two));
+    waits.commit();
+    MyTx::sgl().interpret_string(
+        "(rewrite (Seq (Wait (Secs a)) (Wait (Secs b))) (Wait (Secs (+ a b)))) (run 1)".to_owned(),
+    );
+
+    // waiting is scored by its seconds, so the merged wait of 3 seconds beats two waits
+    let cost_model = TyCosts::new().on::<DurationTy>(|node| node.lit::<f64>(0).unwrap_or(1.0));
+    let best = MyTx::sgl()
+        .extract_with(waits.as_ref(), &GreedyDagExtractor { cost_model })
+        .unwrap();
+    println!("{}", best.to_egglog());
+}
+
+basic_tx_rx_vt!(MyTx);
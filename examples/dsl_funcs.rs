@@ -0,0 +1,37 @@
+use egglog_macros::{egglog_func, egglog_ty};
+use egglog_wrapper::basic_tx_rx_vt;
+
+#[egglog_ty]
+enum Layer {
+    Named { name: String },
+}
+
+#[egglog_ty]
+enum Timeline {
+    At { frame: i64 },
+}
+
+#[egglog_func(output=Timeline)]
+struct CurrentTimeline {
+    layer: Layer,
+}
+
+fn main() {
+    for i in 0..5 {
+        let layer = Layer::<MyTx>::new_named(format!("layer{i}"));
+        let timeline = Timeline::new_at(i * 10);
+        layer.commit();
+        timeline.commit();
+        CurrentTimeline::<MyTx>::set((&layer,), &timeline);
+    }
+
+    // the second page of two rows
+    for ((layer,), timeline) in CurrentTimeline::<MyTx>::get_page(2, Some(2)) {
+        println!("{} -> {}", layer.to_egglog(), timeline.to_egglog());
+    }
+    // every row, the table is read page by page and rows are typed as they are iterated
+    let rows = CurrentTimeline::<MyTx>::iter_all().count();
+    assert_eq!(rows, 5);
+}
+
+basic_tx_rx_vt!(MyTx);
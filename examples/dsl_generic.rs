@@ -0,0 +1,64 @@
+use egglog_macros::{egglog_func, egglog_ty};
+use egglog_wrapper::basic_tx_rx_vt;
+
+#[egglog_ty]
+enum Ctl {
+    Stop {},
+    Go { speed: i64 },
+}
+
+#[egglog_ty]
+enum Point {
+    At { x: i64, y: i64 },
+}
+
+// instances are declared as mangled sorts, `List<Ctl>` is `(ListCtl (ConsCtl Ctl ListCtl) (NilCtl))`
+#[egglog_ty]
+enum List<E> {
+    Cons { head: E, tail: List<E> },
+    Nil {},
+}
+
+#[egglog_ty]
+struct Many<E> {
+    v: Vec<E>,
+}
+
+#[egglog_ty]
+struct Pair<A, B> {
+    first: A,
+    second: B,
+}
+
+#[egglog_ty]
+enum Route {
+    Route { ctls: List<Ctl>, points: Many<Point>, end: Pair<Ctl, Point> },
+}
+
+#[egglog_func(output=Route)]
+struct Planned {}
+
+fn main() {
+    let stop = Ctl::<MyTx>::new_stop();
+    let mut go = Ctl::<MyTx>::new_go(3);
+    let nil = List::<MyTx, CtlTy>::new_nil();
+    let tail = List::new_cons(&stop, &nil);
+    let ctls = List::new_cons(&go, &tail);
+
+    let start = Point::<MyTx>::new_at(0, 0);
+    let end = Point::<MyTx>::new_at(4, 2);
+    let points = Many::<MyTx, PointTy>::new(vec![&start, &end]);
+    let last = Pair::new(&stop, &end);
+
+    let route = Route::new_route(&ctls, &points, &last);
+    println!("{}", ctls.to_egglog());
+    println!("{}", last.to_egglog());
+    route.commit();
+
+    go.set_speed(5).stage();
+    route.commit();
+    Planned::<MyTx>::set((), &route);
+    println!("{}", Planned::<MyTx>::get(()).to_egglog());
+}
+
+basic_tx_rx_vt!(MyTx);
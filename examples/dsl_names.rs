@@ -0,0 +1,62 @@
+use egglog_wrapper::{basic_tx_rx_vt, collect_string_type_defs, wrap::*};
+
+// both modules declare `Point` and a `Value` variant, they are kept apart by custom names
+mod geo {
+    use egglog_macros::egglog_ty;
+
+    #[egglog_ty(name = "geo.Point", prefix = "geo_")]
+    pub enum Point {
+        At { x: i64, y: i64 },
+        #[egglog(name = "Origin")]
+        Value {},
+    }
+}
+
+mod pixel {
+    use egglog_macros::egglog_ty;
+
+    #[egglog_ty(name = "pixel.Point", prefix = "pixel_")]
+    pub enum Point {
+        At { x: i64, y: i64 },
+        Value { rgb: i64 },
+    }
+}
+
+// only sorts are renamed, syms of their nodes are named after sorts so `shape.point0` and
+// `layout.point0` don't clash
+mod shape {
+    use egglog_macros::egglog_ty;
+
+    #[egglog_ty(name = "shape.Point")]
+    pub enum Point {
+        Corner { x: i64, y: i64 },
+    }
+}
+
+mod layout {
+    use egglog_macros::egglog_ty;
+
+    #[egglog_ty(name = "layout.Point")]
+    pub enum Point {
+        Anchor { idx: i64 },
+    }
+}
+
+fn main() {
+    // constructors are prefixed, such as `geo_At` and `pixel_Value`
+    println!("{}", collect_string_type_defs());
+    let origin = geo::Point::<MyTx>::new_value();
+    let red = pixel::Point::<MyTx>::new_value(0xff0000);
+    println!("{}", origin.to_egglog());
+    println!("{}", red.to_egglog());
+    origin.commit();
+    red.commit();
+
+    let corner = shape::Point::<MyTx>::new_corner(1, 2);
+    let anchor = layout::Point::<MyTx>::new_anchor(0);
+    assert_ne!(corner.cur_sym(), anchor.cur_sym());
+    corner.commit();
+    anchor.commit();
+}
+
+basic_tx_rx_vt!(MyTx);
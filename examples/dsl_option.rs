@@ -0,0 +1,34 @@
+use egglog_macros::{egglog_func, egglog_ty};
+use egglog_wrapper::basic_tx_rx_vt;
+
+#[egglog_ty]
+enum HitBox {
+    Rect { w: f64, h: f64 },
+}
+
+#[egglog_ty]
+enum BRabject {
+    Sprite { hit_box: Option<HitBox>, layer: Option<i32> },
+    Group { first: Box<BRabject>, second: Option<BRabject> },
+}
+
+#[egglog_func(output=BRabject)]
+struct Picked {}
+
+fn main() {
+    let hit_box = HitBox::<MyTx>::new_rect(1.0, 2.5);
+    let mut sprite = BRabject::<MyTx>::new_sprite(None, Some(3));
+    let group = BRabject::new_group(&sprite, None);
+    group.commit();
+
+    // optional children take part in version control once they are set
+    sprite.set_hit_box(Some(hit_box.as_ref())).stage();
+    group.commit();
+    Picked::<MyTx>::set((), &group);
+
+    let picked = Picked::<MyTx>::get(());
+    println!("{}", picked.to_egglog());
+    MyTx::sgl().to_dot("egraph.dot".into());
+}
+
+basic_tx_rx_vt!(MyTx);
@@ -0,0 +1,38 @@
+use egglog_macros::{egglog_func, egglog_ty};
+use egglog_wrapper::basic_tx_rx_vt;
+
+#[egglog_ty]
+enum Pixel {
+    Gray { level: u8, alpha: f32 },
+    Pos { x: i32, y: i16, index: u32 },
+    Flag { on: bool },
+}
+
+#[egglog_ty]
+struct Levels {
+    v: Vec<u16>,
+}
+
+#[egglog_ty]
+enum Image {
+    Img { pixel: Pixel, levels: Levels, id: i64 },
+}
+
+#[egglog_func(output=Image)]
+struct Current {}
+
+fn main() {
+    let pixel = Pixel::<MyTx>::new_pos(-3, 7, 42);
+    let levels = Levels::new(vec![1, 2, u16::MAX]);
+    let image = Image::new_img(&pixel, &levels, 1 << 40);
+    image.commit();
+    Current::<MyTx>::set((), &image);
+
+    // widths are restored when the term is read back
+    let read = Current::<MyTx>::get(());
+    println!("{}", read.to_egglog());
+    let _gray = Pixel::<MyTx>::new_gray(128, 0.5);
+    let _flag = Pixel::<MyTx>::new_flag(true);
+}
+
+basic_tx_rx_vt!(MyTx);
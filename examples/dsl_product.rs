@@ -0,0 +1,41 @@
+use egglog_macros::{egglog_func, egglog_ty};
+use egglog_wrapper::basic_tx_rx_vt;
+
+#[egglog_ty]
+struct Srgba {
+    red: f64,
+    green: f64,
+    blue: f64,
+    alpha: f64,
+}
+
+#[egglog_ty]
+struct Sprite {
+    name: String,
+    color: Srgba,
+    shadow: Option<Srgba>,
+}
+
+#[egglog_func(output = Sprite)]
+struct Player {}
+
+fn main() {
+    let red = Srgba::<MyTx>::new(1.0, 0.0, 0.0, 1.0);
+    let mut sprite = Sprite::new("player".to_owned(), &red, None);
+    println!("{}", sprite.to_egglog());
+    sprite.commit();
+
+    // fields are set like variant fields of enum, edits are versioned once staged
+    let grey = Srgba::new(0.5, 0.5, 0.5, 0.5);
+    sprite.set_shadow(Some(&grey)).stage();
+    sprite.commit();
+    assert_eq!(sprite.name_sym(), "player");
+    assert!(sprite.shadow_sym().is_some());
+
+    Player::<MyTx>::set((), &sprite);
+    let read = Player::<MyTx>::get(());
+    println!("{}", read.to_egglog());
+    assert_eq!(read.name_sym(), "player");
+}
+
+basic_tx_rx_vt!(MyTx);
@@ -0,0 +1,43 @@
+use egglog_macros::egglog_ty;
+use egglog_wrapper::{basic_tx_rx_vt, pattern::Pattern};
+
+#[egglog_ty]
+enum Point {
+    At { x: i64, y: i64 },
+    CurAnchorOf { shape: String },
+    OffsetPoint { base: Box<Point>, dx: i64, dy: i64 },
+}
+
+fn main() {
+    let circle = Point::<MyTx>::new_cur_anchor_of("circle".to_owned());
+    let rect = Point::new_cur_anchor_of("rect".to_owned());
+    let origin = Point::new_at(0, 0);
+    let offsets = [
+        Point::new_offset_point(&circle, 1, 0),
+        Point::new_offset_point(&rect, 0, 3),
+        Point::new_offset_point(&origin, 4, 4),
+    ];
+    for offset in &offsets {
+        offset.commit();
+    }
+
+    // offsets of anchors, the offset of origin is not matched and dy is matched but not read
+    let pattern = Pattern::new("(= p (OffsetPoint (CurAnchorOf shape) dx dy))")
+        .var::<Point<MyTx>>("p")
+        .var::<String>("shape")
+        .var::<i64>("dx");
+    for binding in MyTx::sgl().query(&pattern, None) {
+        let p = binding.get::<Point<MyTx>>("p");
+        println!(
+            "{} moved by {}: {}",
+            binding.get::<String>("shape"),
+            binding.get::<i64>("dx"),
+            p.to_egglog()
+        );
+    }
+    // committed nodes are referred by their terms
+    let of_rect = Pattern::new("(OffsetPoint base dx dy)").eq("base", &rect);
+    assert_eq!(MyTx::sgl().query(&of_rect, None).count(), 1);
+}
+
+basic_tx_rx_vt!(MyTx);
@@ -0,0 +1,23 @@
+use egglog_macros::egglog_ty;
+use egglog_wrapper::basic_tx_vt;
+
+#[egglog_ty]
+enum Cons {
+    Value(i64, Box<Cons>),
+    End,
+}
+
+fn main() {
+    let end = Cons::<MyTx>::new_end();
+    let mut node1 = Cons::new_value(1, &end);
+    let node2 = Cons::new_value(2, &node1);
+    // same datatype as `Value { v: i64, con: Box<Cons> }`, fields are set by position
+    println!("{}", node2.to_egglog());
+    node2.commit();
+    node1.set_0(3).stage();
+    node2.commit();
+    println!("node1's value is stored in {}", node1._1_sym());
+    MyTx::sgl().to_dot("egraph.dot".into());
+}
+
+basic_tx_vt!(MyTx);
@@ -0,0 +1,34 @@
+use egglog_macros::egglog_ty;
+use egglog_wrapper::basic_tx_vt;
+
+#[egglog_ty]
+enum Ctl {
+    Wait { secs: f64 },
+    Seq { vec_ctl: VecCtl },
+}
+
+#[egglog_ty]
+struct VecCtl {
+    v: Vec<Ctl>,
+}
+
+fn main() {
+    let wait1 = Ctl::<MyTx>::new_wait(1.0);
+    let wait2 = Ctl::<MyTx>::new_wait(2.0);
+    let wait3 = Ctl::<MyTx>::new_wait(3.0);
+    let mut timeline = VecCtl::new(vec![&wait1]);
+    let seq = Ctl::new_seq(&timeline);
+    seq.commit();
+
+    // every edit is staged and committed like a setter of enum node
+    timeline.push(&wait2).insert(0, &wait3).stage();
+    seq.commit();
+    timeline.replace(1, &wait2).remove(2).stage();
+    seq.commit();
+    timeline.set_all(vec![&wait1, &wait2, &wait3]).stage();
+    seq.commit();
+    println!("{}", timeline.to_egglog());
+    MyTx::sgl().to_dot("egraph.dot".into());
+}
+
+basic_tx_vt!(MyTx);
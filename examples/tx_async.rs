@@ -0,0 +1,35 @@
+use egglog_macros::egglog_ty;
+use egglog_wrapper::basic_tx_async;
+
+#[egglog_ty]
+enum Cons {
+    Value { v: i64, con: Box<Cons> },
+    End {},
+}
+
+#[egglog_ty]
+struct VecCon {
+    v: Vec<Cons>,
+}
+
+#[egglog_ty]
+enum Root {
+    V { v: VecCon },
+}
+
+#[tokio::main]
+async fn main() {
+    let node1 = Cons::new_value(1, &Cons::<MyTx>::new_end());
+    let mut node2 = Cons::new_value(2, &node1);
+    let root = Root::new_v(&VecCon::new(vec![&node2]));
+    // commit returns immediately, egraph is rebuilt by the worker thread
+    root.commit();
+    node2.set_v(4).stage();
+    root.commit();
+
+    // wait until the worker has interpreted both commits
+    MyTx::sgl().flushed().await.expect("egraph failed to run commits");
+    MyTx::sgl().to_dot("egraph.dot".into());
+}
+
+basic_tx_async!(MyTx);
@@ -0,0 +1,52 @@
+use egglog_macros::{egglog_func, egglog_ty};
+use egglog_wrapper::{basic_tx_mock, tx_mock::MockCall};
+
+#[egglog_ty]
+enum Cons {
+    Value { v: i64, con: Box<Cons> },
+    End {},
+}
+
+#[egglog_ty]
+struct VecCon {
+    v: Vec<Cons>,
+}
+
+#[egglog_ty]
+enum Root {
+    V { v: VecCon },
+}
+
+#[egglog_func(output=Root)]
+struct Selected {}
+
+fn main() {
+    let end = Cons::<MyTx>::new_end();
+    let mut node = Cons::new_value(1, &end);
+    let root = Root::new_v(&VecCon::new(vec![&node]));
+    node.set_v(2).stage();
+    root.commit();
+    Selected::<MyTx>::set((), &root);
+
+    let calls = MyTx::sgl().take_calls();
+    assert_eq!(MyTx::sgl().calls(), vec![]);
+    assert!(matches!(calls[0], MockCall::New { sym, .. } if sym == end.cur_sym()));
+    assert_eq!(calls.len(), 8);
+    assert_eq!(
+        calls[4..],
+        [
+            MockCall::Set { sym: node.cur_sym(), egglog: node.to_egglog() },
+            MockCall::Stage { sym: node.cur_sym() },
+            MockCall::Commit { sym: root.cur_sym() },
+            MockCall::FuncSet { func: "Selected", input: vec![], output: root.cur_sym() },
+        ]
+    );
+
+    // Rx answers are scripted in egglog term syntax, nodes of the answer are created by MyTx
+    MyTx::sgl().script_func_get::<Selected<MyTx>>("(V (vec-of (Value 3 (End))))");
+    let selected = Selected::<MyTx>::get(());
+    assert_eq!(MyTx::sgl().created().len(), 4);
+    println!("{}", selected.to_egglog());
+}
+
+basic_tx_mock!(MyTx);
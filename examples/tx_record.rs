@@ -0,0 +1,39 @@
+use egglog_macros::{egglog_func, egglog_ty};
+use egglog_wrapper::{basic_tx_record, tx_record::TxRecord};
+
+#[egglog_ty]
+enum Cons {
+    Value { v: i64, con: Box<Cons> },
+    End {},
+}
+
+#[egglog_ty]
+struct VecCon {
+    v: Vec<Cons>,
+}
+
+#[egglog_ty]
+enum Root {
+    V { v: VecCon },
+}
+
+#[egglog_func(output=Root)]
+struct Selected {}
+
+fn main() {
+    let node1 = Cons::new_value(1, &Cons::<MyTx>::new_end());
+    let mut node2 = Cons::new_value(2, &node1);
+    let root = Root::new_v(&VecCon::new(vec![&node2]));
+    root.commit();
+    node2.set_v(4).stage();
+    root.commit();
+    Selected::<MyTx>::set((), &root);
+
+    // the transcript could also be run by `egglog transcript.egg`
+    MyTx::sgl().save("transcript.egg").unwrap();
+    let replayed = TxRecord::load("transcript.egg").unwrap();
+    MyTx::sgl().assert_same_egraph(&replayed);
+    println!("{}", MyTx::sgl().transcript());
+}
+
+basic_tx_record!(MyTx);
@@ -0,0 +1,47 @@
+use std::{net::TcpListener, thread};
+
+use egglog_macros::{egglog_func, egglog_ty};
+use egglog_wrapper::{basic_tx_remote, tx_remote::serve};
+
+#[egglog_ty]
+enum Cons {
+    Value { v: i64, con: Box<Cons> },
+    End {},
+}
+
+#[egglog_ty]
+struct VecCon {
+    v: Vec<Cons>,
+}
+
+#[egglog_ty]
+enum Root {
+    V { v: VecCon },
+}
+
+#[egglog_func(output=Root)]
+struct Selected {}
+
+const ADDR: &str = "127.0.0.1:7878";
+
+fn main() {
+    // the server could also be started by `cargo run --bin egraph_server tcp 127.0.0.1:7878`
+    let listener = TcpListener::bind(ADDR).expect("failed to bind egraph server");
+    thread::spawn(move || serve(listener.incoming()));
+
+    let node1 = Cons::new_value(1, &Cons::<MyTx>::new_end());
+    let mut node2 = Cons::new_value(2, &node1);
+    let root = Root::new_v(&VecCon::new(vec![&node2]));
+    root.commit();
+    node2.set_v(4).stage();
+    root.commit();
+
+    Selected::<MyTx>::set((), &root);
+    let selected = Selected::<MyTx>::get(());
+    println!("selected root is {}", selected.cur_sym());
+    for (_, output) in Selected::<MyTx>::get_all(None) {
+        println!("row of Selected: {}", output.cur_sym());
+    }
+}
+
+basic_tx_remote!(MyTx, ADDR);
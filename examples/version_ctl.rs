@@ -37,6 +37,9 @@ fn main() {
     root.locate_latest();
     Selected::<MyTx>::set((), &root);
     MyTx::sgl().to_dot("egraph1.dot".into());
+    // egraph and version DAG of work area, for inspectors
+    MyTx::sgl().to_json("egraph.json".into());
+    MyTx::sgl().work_area_to_json("work_area.json".into());
 }
 
 basic_tx_vt!(MyTx);
@@ -0,0 +1,65 @@
+use egglog_macros::egglog_ty;
+use egglog_wrapper::basic_tx_rx_vt;
+
+#[egglog_ty]
+enum Toggle {
+    On { visible: bool },
+    Marker { tick: () },
+}
+
+#[egglog_ty]
+enum Panel {
+    Item { toggle: Toggle, enabled: bool },
+}
+
+#[test]
+fn bools_render_as_egglog_literals() {
+    let mut toggle = Toggle::<MyTx>::new_on(true);
+    assert_eq!(
+        toggle.to_egglog(),
+        format!("(let {} (On true))", toggle.cur_sym())
+    );
+    toggle.set_visible(false);
+    assert_eq!(
+        toggle.to_egglog(),
+        format!("(let {} (On false))", toggle.cur_sym())
+    );
+
+    let panel = Panel::new_item(&toggle, true);
+    assert_eq!(
+        panel.to_egglog(),
+        format!("(let {} (Item {} true))", panel.cur_sym(), toggle.cur_sym())
+    );
+    // bool fields are literals, only the toggle is a succ
+    assert_eq!(panel.succs(), vec![toggle.cur_sym()]);
+}
+
+#[test]
+fn units_render_as_egglog_unit() {
+    let marker = Toggle::<MyTx>::new_marker(());
+    assert_eq!(
+        marker.to_egglog(),
+        format!("(let {} (Marker ()))", marker.cur_sym())
+    );
+    assert!(marker.succs().is_empty());
+}
+
+#[test]
+fn set_bools_are_read_by_egraph() {
+    let mut toggle = Toggle::<MyTx>::new_on(true);
+    let panel = Panel::new_item(&toggle, false);
+    panel.commit();
+    let shown = Toggle::<MyTx>::new_on(true);
+    let hidden = Toggle::<MyTx>::new_on(false);
+    shown.commit();
+    hidden.commit();
+    assert!(MyTx::sgl().are_equal(&toggle, &shown));
+
+    toggle.set_visible(false).stage();
+    panel.commit();
+    toggle.locate_latest();
+    assert!(MyTx::sgl().are_equal(&toggle, &hidden));
+    assert!(!MyTx::sgl().are_equal(&toggle, &shown));
+}
+
+basic_tx_rx_vt!(MyTx);
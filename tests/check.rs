@@ -0,0 +1,73 @@
+use egglog_macros::{egglog_func, egglog_ty};
+use egglog_wrapper::{basic_tx_rx_vt, check, check::Fact};
+
+#[egglog_ty]
+enum Expr {
+    Num { n: i64 },
+    Var { name: String },
+    Mul { l: Box<Expr>, r: Box<Expr> },
+    Shl { l: Box<Expr>, bits: i64 },
+}
+
+#[egglog_func(output=Expr)]
+struct Optimized {}
+
+#[egglog_func(output=Expr)]
+struct Twice {
+    e: Expr,
+}
+
+#[test]
+fn check_macro_parses_facts() {
+    let x = Expr::<MyTx>::new_var("x".to_owned());
+    let shift = Expr::new_shl(&x, 1);
+    assert_eq!(
+        check!(Optimized::apply(()) == shift),
+        Fact::Eq("(Optimized)".to_owned(), shift.term())
+    );
+    // tokens of lhs are collected up to `==`, including generic args and tuples
+    assert_eq!(
+        check!(Twice::<MyTx>::apply((&x,)) == Optimized::apply(())),
+        Fact::Eq(format!("(Twice {})", x.term()), "(Optimized)".to_owned())
+    );
+    assert_eq!(
+        check!(Optimized::<MyTx>::apply(())),
+        Fact::Holds("(Optimized)".to_owned())
+    );
+    assert_eq!(
+        check!(Twice::<MyTx>::apply((&x,))).to_string(),
+        format!("(Twice {})", x.term())
+    );
+    assert_eq!(
+        check!(Optimized::apply(()) == x).to_string(),
+        format!("(= (Optimized) {})", x.term())
+    );
+}
+
+#[test]
+fn check_against_egraph() {
+    let x = Expr::<MyTx>::new_var("y".to_owned());
+    let double = Expr::new_mul(&x, &Expr::new_num(2));
+    let shift = Expr::new_shl(&x, 1);
+    double.commit();
+    shift.commit();
+    // function without row has no value
+    let failure = MyTx::sgl()
+        .check(check!(Optimized::<MyTx>::apply(())))
+        .unwrap_err();
+    assert!(failure.reason.contains("has no value"), "{failure}");
+
+    Optimized::<MyTx>::set((), &double);
+    let failure = MyTx::sgl()
+        .check(check!(Optimized::apply(()) == shift))
+        .unwrap_err();
+    assert_eq!(failure.fact, check!(Optimized::apply(()) == shift));
+    assert!(failure.reason.contains("not proven equal"), "{failure}");
+
+    MyTx::sgl().interpret_string("(rewrite (Mul a (Num 2)) (Shl a 1)) (run 1)".to_owned());
+    MyTx::sgl()
+        .check(check!(Optimized::apply(()) == shift))
+        .unwrap();
+}
+
+basic_tx_rx_vt!(MyTx);
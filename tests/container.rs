@@ -0,0 +1,89 @@
+use egglog_macros::egglog_ty;
+use egglog_wrapper::basic_tx_vt;
+use std::collections::{BTreeMap, HashSet};
+
+#[egglog_ty]
+enum Layer {
+    Named { name: String, depth: i64 },
+}
+
+#[egglog_ty]
+struct LayerSet {
+    layers: HashSet<Layer>,
+}
+
+#[egglog_ty]
+struct LayerByDepth {
+    layers: BTreeMap<i64, Layer>,
+}
+
+#[egglog_ty]
+enum Scene {
+    Scene {
+        layers: LayerSet,
+        by_depth: LayerByDepth,
+    },
+}
+
+#[test]
+fn set_and_map_render_egglog_containers() {
+    let ground = Layer::<MyTx>::new_named("ground".to_owned(), 0);
+    let sky = Layer::<MyTx>::new_named("sky".to_owned(), 10);
+    let layer_set = LayerSet::new(vec![&ground, &sky]);
+    let by_depth = LayerByDepth::new(vec![(0, &ground as &dyn AsRef<_>), (10, &sky)]);
+    assert_eq!(
+        layer_set.to_egglog(),
+        format!(
+            "(let {} (set-of {} {}))",
+            layer_set.cur_sym(),
+            ground.cur_sym(),
+            sky.cur_sym()
+        )
+    );
+    assert_eq!(
+        by_depth.to_egglog(),
+        format!(
+            "(let {} (map-insert (map-insert (map-empty) 0 {}) 10 {}))",
+            by_depth.cur_sym(),
+            ground.cur_sym(),
+            sky.cur_sym()
+        )
+    );
+    assert_eq!(layer_set.succs(), vec![ground.cur_sym(), sky.cur_sym()]);
+    assert_eq!(by_depth.succs(), vec![ground.cur_sym(), sky.cur_sym()]);
+}
+
+#[test]
+fn set_and_map_follow_element_versions() {
+    let mut ground = Layer::<MyTx>::new_named("ground".to_owned(), 0);
+    let sky = Layer::<MyTx>::new_named("sky".to_owned(), 10);
+    let mut layer_set = LayerSet::new(vec![&ground, &sky]);
+    let mut by_depth = LayerByDepth::new(vec![(0, &ground as &dyn AsRef<_>), (10, &sky)]);
+    let mut scene = Scene::new_scene(&layer_set, &by_depth);
+    scene.commit();
+    let (first_ground, first_set, first_map) =
+        (ground.cur_sym(), layer_set.cur_sym(), by_depth.cur_sym());
+
+    ground.set_depth(-1).stage();
+    scene.commit();
+    let latest_ground = MyTx::sgl().locate_latest(first_ground);
+    assert_ne!(latest_ground, first_ground);
+
+    // containers holding the element got new versions, which refer to the new element
+    layer_set.locate_latest();
+    by_depth.locate_latest();
+    assert_ne!(layer_set.cur_sym(), first_set);
+    assert_ne!(by_depth.cur_sym(), first_map);
+    assert_eq!(layer_set.succs(), vec![latest_ground, sky.cur_sym()]);
+    assert_eq!(by_depth.succs(), vec![latest_ground, sky.cur_sym()]);
+    scene.locate_latest();
+    assert_eq!(scene.succs(), vec![layer_set.cur_sym(), by_depth.cur_sym()]);
+
+    layer_set.locate_prev();
+    by_depth.locate_prev();
+    assert_eq!(layer_set.cur_sym(), first_set);
+    assert_eq!(layer_set.succs(), vec![first_ground, sky.cur_sym()]);
+    assert_eq!(by_depth.succs(), vec![first_ground, sky.cur_sym()]);
+}
+
+basic_tx_vt!(MyTx);
@@ -0,0 +1,86 @@
+use egglog_macros::{egglog_func, egglog_ty};
+use egglog_wrapper::{basic_tx_no_vt, basic_tx_rx_vt};
+
+#[egglog_ty]
+enum Expr {
+    Num {
+        n: i64,
+    },
+    Add {
+        l: Box<Expr>,
+        r: Box<Expr>,
+    },
+    Small {
+        n: i64,
+    },
+    #[egglog(cost = 100)]
+    Big {
+        n: i64,
+    },
+}
+
+#[egglog_func(output=Expr)]
+struct BestSmall {}
+
+#[egglog_func(output=Expr)]
+struct BestBig {}
+
+#[egglog_func(output=Expr)]
+struct Twice {
+    e: Expr,
+}
+
+#[egglog_func(output=Expr, cost=100)]
+struct Doubled {
+    e: Expr,
+}
+
+#[test]
+fn variant_cost_changes_extracted_node() {
+    let small = Expr::<RxTx>::new_small(3);
+    let big = Expr::<RxTx>::new_big(7);
+    small.commit();
+    big.commit();
+    BestSmall::<RxTx>::set((), &small);
+    BestBig::<RxTx>::set((), &big);
+    RxTx::sgl().interpret_string(
+        "(rewrite (Small n) (Add (Num n) (Num 0)))
+         (rewrite (Big n) (Add (Num n) (Num 0)))
+         (run 1)"
+            .to_owned(),
+    );
+
+    // both sides are equivalent, only the cost of Big makes the sum cheaper
+    assert!(matches!(
+        BestSmall::<RxTx>::get(()).node.ty,
+        ExprInner::Small { n: 3 }
+    ));
+    match BestBig::<RxTx>::get(()).node.ty {
+        ExprInner::Add { .. } => {}
+        ty => panic!("expect sum extracted for Big, found {ty:?}"),
+    }
+}
+
+#[test]
+fn function_cost_changes_extracted_term() {
+    let tx = NoVtTx::sgl();
+    let four = Expr::<NoVtTx>::new_num(4);
+    let five = Expr::<NoVtTx>::new_num(5);
+    let four_sum = Expr::new_add(&four, &four);
+    let five_sum = Expr::new_add(&five, &five);
+    Twice::<NoVtTx>::set((&four,), &four_sum);
+    Doubled::<NoVtTx>::set((&five,), &five_sum);
+
+    // calls of functions are terms of their output eclass, costing 1 unless annotated
+    assert_eq!(
+        tx.extract_term(four_sum.cur_sym().as_str()),
+        Ok("(Twice (Num 4))".to_owned())
+    );
+    assert_eq!(
+        tx.extract_term(five_sum.cur_sym().as_str()),
+        Ok("(Add (Num 5) (Num 5))".to_owned())
+    );
+}
+
+basic_tx_rx_vt!(RxTx);
+basic_tx_no_vt!(NoVtTx);
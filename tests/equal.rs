@@ -0,0 +1,29 @@
+use egglog_macros::egglog_ty;
+use egglog_wrapper::basic_tx_rx_vt;
+
+#[egglog_ty]
+enum Ctl {
+    Act { name: String },
+    Seq { inner: Box<Ctl> },
+    Para { inner: Box<Ctl> },
+}
+
+#[test]
+fn equal_after_rewrite() {
+    let x = Ctl::<MyTx>::new_act("fade".to_owned());
+    let para = Ctl::new_para(&Ctl::new_seq(&x));
+    para.commit();
+    assert!(!MyTx::sgl().are_equal(&para, &x));
+    assert_ne!(MyTx::sgl().eclass_of(&para), MyTx::sgl().eclass_of(&x));
+
+    // the same term committed by another node is hashconsed into the same eclass
+    let same = Ctl::<MyTx>::new_act("fade".to_owned());
+    same.commit();
+    assert!(MyTx::sgl().are_equal(&x, &same));
+
+    MyTx::sgl().interpret_string("(rewrite (Para (Seq x)) x) (run 1)".to_owned());
+    assert!(MyTx::sgl().are_equal(&para, &x));
+    assert_eq!(MyTx::sgl().eclass_of(&para), MyTx::sgl().eclass_of(&same));
+}
+
+basic_tx_rx_vt!(MyTx);
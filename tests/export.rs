@@ -0,0 +1,91 @@
+use egglog_macros::egglog_ty;
+use egglog_wrapper::{
+    basic_tx_vt,
+    export::work_area_json,
+    wrap::{EgglogNode, Sym, ToEgglog, WorkAreaNode},
+};
+
+#[egglog_ty]
+enum Cons {
+    Value { v: i64, con: Box<Cons> },
+    End {},
+}
+
+/// node written by hand, it tells neither its sort nor its fields
+#[derive(Clone)]
+struct Opaque(Sym);
+impl ToEgglog for Opaque {
+    fn to_egglog(&self) -> String {
+        format!("(let {} (Opaque))", self.0)
+    }
+}
+impl EgglogNode for Opaque {
+    fn succs_mut(&mut self) -> Vec<&mut Sym> {
+        vec![]
+    }
+    fn succs(&self) -> Vec<Sym> {
+        vec![]
+    }
+    fn next_sym(&mut self) -> Sym {
+        self.0
+    }
+    fn cur_sym(&self) -> Sym {
+        self.0
+    }
+    fn cur_sym_mut(&mut self) -> &mut Sym {
+        &mut self.0
+    }
+    fn clone_dyn(&self) -> Box<dyn EgglogNode> {
+        Box::new(self.clone())
+    }
+}
+
+#[test]
+fn work_area_json_links() {
+    let end = Cons::<MyTx>::new_end();
+    let list = Cons::new_value(2, &end);
+    let next = Cons::new_value(3, &end);
+
+    let mut end_node = WorkAreaNode::new(end.clone_dyn());
+    end_node.preds.push(list.cur_sym());
+    let mut list_node = WorkAreaNode::new(list.clone_dyn());
+    list_node.next = Some(next.cur_sym());
+    let mut next_node = WorkAreaNode::new(next.clone_dyn());
+    next_node.prev = Some(list.cur_sym());
+
+    let json = work_area_json([&end_node, &list_node, &next_node]);
+    let (end, list, next) = (end.cur_sym(), list.cur_sym(), next.cur_sym());
+    // nodes are ordered by sym
+    assert_eq!(
+        json,
+        format!(
+            concat!(
+                "{{\"nodes\":[",
+                "{{\"sym\":\"{end}\",\"sort\":\"Cons\",\"ctor\":\"End\",\"fields\":[],\"term\":\"(End)\",",
+                "\"preds\":[\"{list}\"],\"succs\":[],\"next\":null,\"prev\":null}},",
+                "{{\"sym\":\"{list}\",\"sort\":\"Cons\",\"ctor\":\"Value\",\"fields\":[\"2\",\"{end}\"],",
+                "\"term\":\"(Value 2 {end})\",\"preds\":[],\"succs\":[\"{end}\"],\"next\":\"{next}\",\"prev\":null}},",
+                "{{\"sym\":\"{next}\",\"sort\":\"Cons\",\"ctor\":\"Value\",\"fields\":[\"3\",\"{end}\"],",
+                "\"term\":\"(Value 3 {end})\",\"preds\":[],\"succs\":[\"{end}\"],\"next\":null,\"prev\":\"{list}\"}}",
+                "]}}"
+            ),
+            end = end,
+            list = list,
+            next = next,
+        )
+    );
+}
+
+#[test]
+fn work_area_json_of_hand_written_node() {
+    let opaque = WorkAreaNode::new(Box::new(Opaque(Sym::new("opaque0".into()))));
+    assert_eq!(
+        work_area_json([&opaque]),
+        concat!(
+            "{\"nodes\":[{\"sym\":\"opaque0\",\"sort\":\"\",\"ctor\":null,\"fields\":null,",
+            "\"term\":\"(let opaque0 (Opaque))\",\"preds\":[],\"succs\":[],\"next\":null,\"prev\":null}]}"
+        )
+    );
+}
+
+basic_tx_vt!(MyTx);
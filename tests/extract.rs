@@ -0,0 +1,127 @@
+use egglog_wrapper::extract::{
+    AnnotatedCost, Cost, CostModel, EClass, ENode, ENodeRef, Extractor, GreedyDagExtractor,
+    SerializedEGraph, TreeExtractor, variants,
+};
+
+fn enode(op: &str, eclass: usize, children: &[usize], cost: Cost) -> ENode {
+    ENode {
+        op: op.to_owned(),
+        eclass,
+        children: children.to_vec(),
+        cost,
+    }
+}
+
+/// egraph of
+/// ```text
+/// class 0 (root): (Pair c1 c1) cost 1 | (Flat) cost 4
+/// class 1:        (Heavy c2) cost 1
+/// class 2:        3 of i64
+/// class 3:        (Loop c3) cost 1, only reachable through itself
+/// ```
+/// as a tree the pair pays for its shared child twice, as a dag only once
+fn shared_child() -> SerializedEGraph {
+    let nodes = vec![
+        enode("Pair", 0, &[1, 1], 1.0),
+        enode("Flat", 0, &[], 4.0),
+        enode("Heavy", 1, &[2], 1.0),
+        enode("3", 2, &[], 1.0),
+        enode("Loop", 3, &[3], 1.0),
+    ];
+    let mut classes = ["Expr", "Expr", "i64", "Expr"].map(|sort| EClass {
+        sort: sort.to_owned(),
+        nodes: vec![],
+    });
+    for (idx, node) in nodes.iter().enumerate() {
+        classes[node.eclass].nodes.push(idx);
+    }
+    SerializedEGraph {
+        nodes,
+        classes: classes.to_vec(),
+        roots: vec![0],
+    }
+}
+
+#[test]
+fn tree_extractor_counts_shared_children_twice() {
+    let egraph = shared_child();
+    let choices = TreeExtractor { cost_model: AnnotatedCost }.extract(&egraph);
+    assert_eq!(choices[&0], 1);
+    assert_eq!(egraph.term(&choices, 0).unwrap(), "(Flat)");
+    // literals are rendered bare
+    assert_eq!(egraph.term(&choices, 1).unwrap(), "(Heavy 3)");
+    // cyclic class never gets a finite cost
+    assert!(!choices.contains_key(&3));
+    assert_eq!(egraph.term(&choices, 3), None);
+}
+
+#[test]
+fn greedy_dag_extractor_counts_shared_children_once() {
+    let egraph = shared_child();
+    let choices = GreedyDagExtractor { cost_model: AnnotatedCost }.extract(&egraph);
+    assert_eq!(choices[&0], 0);
+    assert_eq!(egraph.term(&choices, 0).unwrap(), "(Pair (Heavy 3) (Heavy 3))");
+    assert!(!choices.contains_key(&3));
+}
+
+#[test]
+fn node_ref_reads_literal_args() {
+    let egraph = shared_child();
+    let heavy = egraph.node(2);
+    assert_eq!(heavy.op(), "Heavy");
+    assert_eq!(heavy.sort(), "Expr");
+    assert_eq!(heavy.arity(), 1);
+    assert_eq!(heavy.lit::<i64>(0), Some(3));
+    assert_eq!(heavy.lit::<i64>(1), None);
+}
+
+#[test]
+fn variants_are_ranked_by_tree_cost() {
+    let egraph = shared_child();
+    assert_eq!(
+        variants(&egraph, &AnnotatedCost, 0, 3),
+        vec![
+            (4.0, "(Flat)".to_owned()),
+            (5.0, "(Pair (Heavy 3) (Heavy 3))".to_owned())
+        ]
+    );
+    assert_eq!(variants(&egraph, &AnnotatedCost, 0, 1).len(), 1);
+    // variants leading back to their class are dropped
+    assert!(variants(&egraph, &AnnotatedCost, 3, 3).is_empty());
+}
+
+/// annotated costs, except that `op` costs `cost`
+struct CostOf {
+    op: &'static str,
+    cost: Cost,
+}
+impl CostModel for CostOf {
+    fn node_cost(&self, node: ENodeRef) -> Cost {
+        if node.op() == self.op {
+            self.cost
+        } else {
+            node.annotated_cost()
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "cost of Loop should be non-negative, found -1")]
+fn negative_cost_is_rejected() {
+    // relaxing the loop would lower cost of class 3 forever
+    let cost_model = CostOf {
+        op: "Loop",
+        cost: -1.0,
+    };
+    TreeExtractor { cost_model }.extract(&shared_child());
+}
+
+#[test]
+#[should_panic(expected = "cost of Heavy should be non-negative, found NaN")]
+fn nan_cost_is_rejected() {
+    let cost_model = CostOf {
+        op: "Heavy",
+        cost: Cost::NAN,
+    };
+    GreedyDagExtractor { cost_model }.extract(&shared_child());
+}
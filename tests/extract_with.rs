@@ -0,0 +1,43 @@
+use egglog_macros::egglog_ty;
+use egglog_wrapper::{
+    basic_tx_rx_vt,
+    extract::{AnnotatedCost, TreeExtractor, TyCosts},
+};
+
+#[egglog_ty]
+enum Anim {
+    Wait { secs: i64 },
+    Seq { a: Box<Anim>, b: Box<Anim> },
+}
+
+#[test]
+fn rust_cost_model_picks_typed_node() {
+    let one = Anim::<MyTx>::new_wait(1);
+    let two = Anim::<MyTx>::new_wait(2);
+    let seq = Anim::new_seq(&one, &two);
+    seq.commit();
+    MyTx::sgl()
+        .interpret_string("(rewrite (Seq (Wait a) (Wait b)) (Wait (+ a b))) (run 1)".to_owned());
+
+    // every constructor costs 1, so the merged wait is cheaper
+    let annotated = TreeExtractor {
+        cost_model: AnnotatedCost,
+    };
+    let merged = MyTx::sgl().extract_with(seq.as_ref(), &annotated).unwrap();
+    assert!(matches!(merged.node.ty, AnimInner::Wait { secs: 3 }));
+
+    // long waits cost the square of their secs, so the sequence of short waits is kept
+    let squared = TreeExtractor {
+        cost_model: TyCosts::new().on::<AnimTy>(|node| match node.op() {
+            "Wait" => node.lit::<i64>(0).map_or(1.0, |secs| (secs * secs) as f64),
+            _ => 1.0,
+        }),
+    };
+    let kept = MyTx::sgl().extract_with(seq.as_ref(), &squared).unwrap();
+    assert!(matches!(kept.node.ty, AnimInner::Seq { .. }));
+    // nodes of extracted term are created by the Tx, the same term is hashconsed into the eclass
+    kept.commit();
+    assert!(MyTx::sgl().are_equal(&kept, &seq));
+}
+
+basic_tx_rx_vt!(MyTx);
@@ -0,0 +1,60 @@
+use egglog_macros::{egglog_func, egglog_ty};
+use egglog_wrapper::basic_tx_rx_vt;
+
+#[egglog_ty]
+enum Cons {
+    Value { v: i64, con: Box<Cons> },
+    End {},
+}
+
+#[egglog_func(output=Cons)]
+struct Tail {
+    list: Cons,
+}
+
+fn head(list: &Cons<MyTx>) -> i64 {
+    match list.node.ty {
+        ConsInner::Value { v, .. } => v,
+        ref ty => panic!("expect Value, found {ty:?}"),
+    }
+}
+
+fn heads(rows: impl IntoIterator<Item = ((Cons<MyTx>,), Cons<MyTx>)>) -> Vec<(i64, i64)> {
+    rows.into_iter()
+        .map(|((list,), output)| (head(&list), head(&output)))
+        .collect()
+}
+
+#[test]
+fn pages_of_rows_are_read_by_offset() {
+    let end = Cons::<MyTx>::new_end();
+    end.commit();
+    for v in 0..5 {
+        let list = Cons::new_value(v, &end);
+        let output = Cons::new_value(v * 10, &end);
+        list.commit();
+        output.commit();
+        Tail::<MyTx>::set((&list,), &output);
+    }
+
+    let all = heads(Tail::<MyTx>::get_all(None));
+    let mut sorted = all.clone();
+    sorted.sort();
+    assert_eq!(sorted, (0..5).map(|v| (v, v * 10)).collect::<Vec<_>>());
+
+    // pages are slices of the table in the same order
+    assert_eq!(heads(Tail::<MyTx>::get_page(1, Some(2))), all[1..3]);
+    assert_eq!(heads(Tail::<MyTx>::get_page(4, Some(2))), all[4..]);
+    assert_eq!(heads(Tail::<MyTx>::get_page(3, None)), all[3..]);
+    assert!(Tail::<MyTx>::get_page(5, Some(2)).is_empty());
+
+    // iteration reads the same rows page by page
+    assert_eq!(heads(Tail::<MyTx>::iter_all()), all);
+    let paged = FuncRows::<Tail<MyTx>, MyTx>::with_page_size(2);
+    assert_eq!(heads(paged), all);
+    // a full last page is followed by an empty one
+    let paged = FuncRows::<Tail<MyTx>, MyTx>::with_page_size(5);
+    assert_eq!(heads(paged), all);
+}
+
+basic_tx_rx_vt!(MyTx);
@@ -0,0 +1,108 @@
+use egglog_macros::egglog_ty;
+use egglog_wrapper::{basic_tx_vt, collect_string_type_defs};
+
+#[egglog_ty]
+enum Ctl {
+    Stop {},
+    Go { speed: i64 },
+}
+
+#[egglog_ty]
+enum Point {
+    At { x: i64, y: i64 },
+}
+
+#[egglog_ty]
+enum List<E> {
+    Cons { head: E, tail: List<E> },
+    Nil {},
+}
+
+#[egglog_ty]
+struct Pair<A, B> {
+    first: A,
+    second: B,
+}
+
+#[egglog_ty]
+enum Route {
+    Route {
+        ctls: List<Ctl>,
+        points: List<Point>,
+        end: Pair<Ctl, List<Point>>,
+    },
+}
+
+fn sort_defs<T: EgglogTy>() -> Vec<String> {
+    let mut defs = SortDefs::default();
+    T::collect_sort_defs(&mut defs);
+    defs.defs().map(str::to_owned).collect()
+}
+
+#[test]
+fn instances_of_generic_get_mangled_sorts() {
+    assert_eq!(ListTy::<CtlTy>::sort_name(), "ListCtl");
+    assert_eq!(ListTy::<PointTy>::sort_name(), "ListPoint");
+    assert_eq!(
+        PairTy::<CtlTy, ListTy<PointTy>>::sort_name(),
+        "PairCtlListPoint"
+    );
+
+    // sorts of type args are collected along with the instance
+    assert_eq!(
+        sort_defs::<ListTy<CtlTy>>(),
+        [
+            "(Ctl (Stop ) (Go i64))",
+            "(ListCtl (ConsCtl Ctl ListCtl) (NilCtl ))"
+        ]
+    );
+    assert_eq!(
+        sort_defs::<ListTy<PointTy>>(),
+        [
+            "(ListPoint (ConsPoint Point ListPoint) (NilPoint ))",
+            "(Point (At i64 i64))"
+        ]
+    );
+    assert!(
+        sort_defs::<PairTy<CtlTy, ListTy<PointTy>>>()
+            .contains(&"(PairCtlListPoint (PairCtlListPoint Ctl ListPoint))".to_owned())
+    );
+}
+
+#[test]
+fn used_instances_are_declared_once() {
+    let defs = collect_string_type_defs();
+    for def in [
+        "(ListCtl (ConsCtl Ctl ListCtl) (NilCtl ))",
+        "(ListPoint (ConsPoint Point ListPoint) (NilPoint ))",
+        "(PairCtlListPoint (PairCtlListPoint Ctl ListPoint))",
+    ] {
+        assert_eq!(defs.matches(def).count(), 1, "{def} in {defs}");
+    }
+}
+
+#[test]
+fn nodes_of_instances_use_mangled_constructors() {
+    let stop = Ctl::<MyTx>::new_stop();
+    let nil = List::<MyTx, CtlTy>::new_nil();
+    let ctls = List::new_cons(&stop, &nil);
+    let at = Point::<MyTx>::new_at(1, 2);
+    let points = List::new_cons(&at, &List::<MyTx, PointTy>::new_nil());
+    assert_eq!(
+        ctls.to_egglog(),
+        format!(
+            "(let {} (ConsCtl {} {}))",
+            ctls.cur_sym(),
+            stop.cur_sym(),
+            nil.cur_sym()
+        )
+    );
+    assert_eq!(ctls.sort_name(), "ListCtl");
+    assert_eq!(points.sort_name(), "ListPoint");
+
+    let end = Pair::new(&stop, &points);
+    let route = Route::new_route(&ctls, &points, &end);
+    route.commit();
+}
+
+basic_tx_vt!(MyTx);
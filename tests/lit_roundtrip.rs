@@ -0,0 +1,113 @@
+use egglog_macros::{egglog_func, egglog_ty};
+use egglog_wrapper::basic_tx_rx_vt;
+use proptest::prelude::*;
+use std::sync::atomic::{AtomicI64, Ordering};
+
+#[egglog_ty]
+enum Lit {
+    F { f: f64 },
+    S { s: String },
+    I8 { v: i8 },
+    I16 { v: i16 },
+    I32 { v: i32 },
+    I64 { v: i64 },
+    U8 { v: u8 },
+    U16 { v: u16 },
+    U32 { v: u32 },
+    F32 { v: f32 },
+}
+
+#[egglog_ty]
+struct Key {
+    id: i64,
+}
+
+#[egglog_func(output=Lit)]
+struct Probe {
+    key: Key,
+}
+
+/// write literal into egraph, extract it back by a function row of its own
+///
+/// functions don't merge outputs, so each literal is keyed by a fresh id
+fn round_trip(lit: &Lit<MyTx>) -> Lit<MyTx> {
+    static NEXT_ID: AtomicI64 = AtomicI64::new(0);
+    let key = Key::<MyTx>::new(NEXT_ID.fetch_add(1, Ordering::Relaxed));
+    key.commit();
+    lit.commit();
+    Probe::<MyTx>::set((&key,), lit);
+    Probe::<MyTx>::get((&key,))
+}
+
+fn float_round_trip(f: f64) -> f64 {
+    match &round_trip(Lit::new_f(f).as_ref()).node.ty {
+        LitInner::F { f } => *f,
+        _ => panic!("expect float"),
+    }
+}
+
+fn string_round_trip(s: &str) -> String {
+    match &round_trip(Lit::new_s(s.to_owned()).as_ref()).node.ty {
+        LitInner::S { s } => s.clone(),
+        _ => panic!("expect string"),
+    }
+}
+
+/// write each value by `$new`, read it back from `$variant`
+macro_rules! assert_round_trip {
+    ($new:ident, $variant:ident, [$($value:expr),*]) => {$(
+        match &round_trip(Lit::$new($value).as_ref()).node.ty {
+            LitInner::$variant { v } => assert_eq!(*v, $value),
+            _ => panic!("expect {}", stringify!($variant)),
+        }
+    )*};
+}
+
+#[test]
+fn integer_bounds() {
+    assert_round_trip!(new_i8, I8, [i8::MIN, -1, 0, i8::MAX]);
+    assert_round_trip!(new_i16, I16, [i16::MIN, -1, 0, i16::MAX]);
+    assert_round_trip!(new_i32, I32, [i32::MIN, -1, 0, i32::MAX]);
+    assert_round_trip!(new_i64, I64, [i64::MIN, -1, 0, i64::MAX]);
+    assert_round_trip!(new_u8, U8, [u8::MIN, u8::MAX]);
+    assert_round_trip!(new_u16, U16, [u16::MIN, u16::MAX]);
+    assert_round_trip!(new_u32, U32, [u32::MIN, u32::MAX]);
+}
+
+#[test]
+fn f32_bounds() {
+    assert_round_trip!(
+        new_f32,
+        F32,
+        [f32::MIN, f32::MIN_POSITIVE, -0.0, 0.1, f32::MAX]
+    );
+    assert_round_trip!(new_f32, F32, [f32::INFINITY, f32::NEG_INFINITY]);
+}
+
+#[test]
+fn special_floats() {
+    assert!(float_round_trip(f64::NAN).is_nan());
+    assert_eq!(float_round_trip(f64::INFINITY), f64::INFINITY);
+    assert_eq!(float_round_trip(f64::NEG_INFINITY), f64::NEG_INFINITY);
+    assert!(float_round_trip(-0.0).is_sign_negative());
+    assert_eq!(float_round_trip(5e-324), 5e-324);
+    assert_eq!(float_round_trip(f64::MAX), f64::MAX);
+}
+
+proptest! {
+    #[test]
+    fn floats(f in any::<f64>()) {
+        let read = float_round_trip(f);
+        prop_assert!(
+            read.to_bits() == f.to_bits() || (read.is_nan() && f.is_nan()),
+            "{:e} is read back as {:e}", f, read
+        );
+    }
+
+    #[test]
+    fn strings(s in "[ \"\\\\();a-z\n\t多]{0,16}") {
+        prop_assert_eq!(string_round_trip(&s), s);
+    }
+}
+
+basic_tx_rx_vt!(MyTx);
@@ -0,0 +1,88 @@
+use egglog_wrapper::wrap::*;
+
+// the modules clash on purpose, so their sorts are only collected here and never sent to an egraph
+mod a {
+    use egglog_macros::egglog_ty;
+
+    #[egglog_ty]
+    pub enum Point {
+        At { x: i64 },
+    }
+}
+
+mod b {
+    use egglog_macros::egglog_ty;
+
+    #[egglog_ty]
+    pub enum Point {
+        At { y: i64 },
+    }
+}
+
+mod c {
+    use egglog_macros::egglog_ty;
+
+    #[egglog_ty(name = "c.Point")]
+    pub enum Point {
+        At { z: i64 },
+    }
+}
+
+mod d {
+    use egglog_macros::egglog_ty;
+
+    #[egglog_ty(name = "POINT")]
+    pub enum Dot {
+        Dot { r: i64 },
+    }
+}
+
+mod geo {
+    use egglog_macros::egglog_ty;
+
+    #[egglog_ty(name = "geo.Point", prefix = "geo_")]
+    pub enum Point {
+        At {
+            x: i64,
+            y: i64,
+        },
+        #[egglog(name = "Origin")]
+        Value {},
+    }
+}
+
+#[test]
+fn conflicts_list_every_duplicate_name() {
+    let mut defs = SortDefs::default();
+    a::PointTy::collect_sort_defs(&mut defs);
+    b::PointTy::collect_sort_defs(&mut defs);
+    c::PointTy::collect_sort_defs(&mut defs);
+    d::DotTy::collect_sort_defs(&mut defs);
+    let funcs = [("At", "names::At"), ("Dot", "names::Dot")];
+    assert_eq!(
+        defs.conflicts(funcs),
+        [
+            "sort `Point` is defined by both `names::a::Point` and `names::b::Point`",
+            "sym prefix `point` is taken by both `names::a::Point` and `names::d::Dot`",
+            "constructor or function `At` is defined by both `names::a::Point` and `names::c::Point`",
+            "constructor or function `At` is defined by both `names::c::Point` and `names::At`",
+            "constructor or function `Dot` is defined by both `names::d::Dot` and `names::Dot`",
+        ]
+    );
+}
+
+#[test]
+fn custom_names_keep_sorts_apart() {
+    let mut defs = SortDefs::default();
+    a::PointTy::collect_sort_defs(&mut defs);
+    geo::PointTy::collect_sort_defs(&mut defs);
+    assert!(defs.conflicts([]).is_empty());
+    assert_eq!(
+        defs.defs().collect::<Vec<_>>(),
+        [
+            "(Point (At i64))",
+            "(geo.Point (geo_At i64 i64) (geo_Origin ))"
+        ]
+    );
+    assert_eq!(geo::PointTy::sort_name(), "geo.Point");
+}
@@ -0,0 +1,79 @@
+use egglog_macros::egglog_ty;
+use egglog_wrapper::basic_tx_vt;
+
+#[egglog_ty]
+enum HitBox {
+    Rect { w: f64, h: f64 },
+}
+
+#[egglog_ty]
+enum BRabject {
+    Sprite {
+        hit_box: Option<HitBox>,
+        layer: Option<i32>,
+    },
+}
+
+#[test]
+fn option_renders_some_and_none() {
+    let hit_box = HitBox::<MyTx>::new_rect(1.0, 2.5);
+    let sprite = BRabject::<MyTx>::new_sprite(Some(hit_box.as_ref()), Some(3));
+    assert_eq!(
+        sprite.to_egglog(),
+        format!(
+            "(let {} (Sprite (SomeHitBox {}) (SomeI64 3)))",
+            sprite.cur_sym(),
+            hit_box.cur_sym()
+        )
+    );
+    assert_eq!(sprite.succs(), vec![hit_box.cur_sym()]);
+
+    let empty = BRabject::<MyTx>::new_sprite(None, None);
+    assert_eq!(
+        empty.to_egglog(),
+        format!("(let {} (Sprite (NoneHitBox) (NoneI64)))", empty.cur_sym())
+    );
+    assert!(empty.succs().is_empty());
+}
+
+#[test]
+fn option_succ_follows_inner_version() {
+    let mut hit_box = HitBox::<MyTx>::new_rect(1.0, 2.5);
+    let mut sprite = BRabject::<MyTx>::new_sprite(Some(hit_box.as_ref()), Some(3));
+    sprite.commit();
+    let first = hit_box.cur_sym();
+
+    hit_box.set_w(4.0).stage();
+    sprite.commit();
+    let second = MyTx::sgl().locate_latest(first);
+    assert_ne!(second, first);
+    sprite.locate_latest();
+    assert_eq!(sprite.succs(), vec![second]);
+    assert!(
+        sprite
+            .to_egglog()
+            .contains(&format!("(SomeHitBox {second})"))
+    );
+
+    // versions are walked back through the inner sym as well
+    sprite.locate_prev();
+    assert_eq!(sprite.succs(), vec![first]);
+}
+
+#[test]
+fn set_option_adds_and_removes_succ() {
+    let hit_box = HitBox::<MyTx>::new_rect(1.0, 2.5);
+    let mut sprite = BRabject::<MyTx>::new_sprite(None, Some(1));
+    sprite.commit();
+
+    sprite.set_hit_box(Some(hit_box.as_ref()));
+    assert_eq!(sprite.succs(), vec![hit_box.cur_sym()]);
+    sprite.set_hit_box(None).set_layer(None);
+    assert!(sprite.succs().is_empty());
+    assert_eq!(
+        sprite.to_egglog(),
+        format!("(let {} (Sprite (NoneHitBox) (NoneI64)))", sprite.cur_sym())
+    );
+}
+
+basic_tx_vt!(MyTx);
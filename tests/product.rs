@@ -0,0 +1,89 @@
+use egglog_macros::egglog_ty;
+use egglog_wrapper::basic_tx_vt;
+
+#[egglog_ty]
+struct Srgba {
+    red: f64,
+    green: f64,
+    blue: f64,
+    alpha: f64,
+}
+
+#[egglog_ty]
+struct Sprite {
+    name: String,
+    color: Srgba,
+    shadow: Option<Srgba>,
+}
+
+#[test]
+fn product_fields_are_read_by_getters() {
+    let red = Srgba::<MyTx>::new(1.0, 0.0, 0.0, 0.5);
+    let sprite = Sprite::new("player".to_owned(), &red, None);
+    assert_eq!(
+        red.to_egglog(),
+        format!("(let {} (Srgba 1.0 0.0 0.0 0.5))", red.cur_sym())
+    );
+    assert_eq!(
+        sprite.to_egglog(),
+        format!(
+            "(let {} (Sprite \"player\" {} (NoneSrgba)))",
+            sprite.cur_sym(),
+            red.cur_sym()
+        )
+    );
+    assert_eq!((red.red_sym(), red.alpha_sym()), (1.0, 0.5));
+    assert_eq!(sprite.name_sym(), "player");
+    assert_eq!(sprite.color_sym().erase(), red.cur_sym());
+    assert!(sprite.shadow_sym().is_none());
+    assert_eq!(sprite.succs(), vec![red.cur_sym()]);
+}
+
+#[test]
+fn product_fields_are_set() {
+    let red = Srgba::<MyTx>::new(1.0, 0.0, 0.0, 1.0);
+    let grey = Srgba::<MyTx>::new(0.5, 0.5, 0.5, 0.5);
+    let mut sprite = Sprite::new("player".to_owned(), &red, None);
+    sprite
+        .set_name("enemy".to_owned())
+        .set_color(&grey)
+        .set_shadow(Some(&red));
+    assert_eq!(sprite.name_sym(), "enemy");
+    *sprite.name_sym_mut() = "boss".to_owned();
+    assert_eq!(
+        sprite.to_egglog(),
+        format!(
+            "(let {} (Sprite \"boss\" {} (SomeSrgba {})))",
+            sprite.cur_sym(),
+            grey.cur_sym(),
+            red.cur_sym()
+        )
+    );
+    assert_eq!(sprite.succs(), vec![grey.cur_sym(), red.cur_sym()]);
+}
+
+#[test]
+fn product_commits_new_versions() {
+    let mut color = Srgba::<MyTx>::new(1.0, 0.0, 0.0, 1.0);
+    let mut sprite = Sprite::new("player".to_owned(), &color, None);
+    sprite.commit();
+    let (first_sprite, first_color) = (sprite.cur_sym(), color.cur_sym());
+
+    color.set_alpha(0.25).stage();
+    sprite.commit();
+    sprite.locate_latest();
+    color.locate_latest();
+    assert_ne!(sprite.cur_sym(), first_sprite);
+    assert_ne!(color.cur_sym(), first_color);
+    assert_eq!(sprite.succs(), vec![color.cur_sym()]);
+
+    // product node is a root of commit as well
+    sprite.set_name("enemy".to_owned()).stage();
+    sprite.commit();
+    assert_ne!(
+        MyTx::sgl().locate_latest(sprite.cur_sym()),
+        sprite.cur_sym()
+    );
+}
+
+basic_tx_vt!(MyTx);
@@ -0,0 +1,62 @@
+use egglog_macros::egglog_ty;
+use egglog_wrapper::{basic_tx_rx_vt, pattern::Pattern};
+
+#[egglog_ty]
+enum Point {
+    At { x: i64, y: i64 },
+    CurAnchorOf { shape: String },
+    OffsetPoint { base: Box<Point>, dx: i64, dy: i64 },
+}
+
+#[test]
+fn pattern_facts() {
+    let rect = Point::<MyTx>::new_cur_anchor_of("rect".to_owned());
+    let pattern = Pattern::new("(OffsetPoint base dx dy)")
+        .var::<i64>("dx")
+        .eq("base", &rect);
+    assert_eq!(
+        pattern.facts(),
+        format!("(OffsetPoint base dx dy) (= base {})", rect.cur_sym())
+    );
+}
+
+#[test]
+fn query_binds_declared_vars() {
+    let circle = Point::<MyTx>::new_cur_anchor_of("circle".to_owned());
+    let origin = Point::new_at(0, 0);
+    let offsets = [
+        Point::new_offset_point(&circle, 1, 0),
+        Point::new_offset_point(&circle, 2, 5),
+        Point::new_offset_point(&origin, 4, 4),
+    ];
+    for offset in &offsets {
+        offset.commit();
+    }
+
+    let pattern = Pattern::new("(= p (OffsetPoint (CurAnchorOf shape) dx dy))")
+        .var::<Point<MyTx>>("p")
+        .var::<String>("shape")
+        .var::<i64>("dx");
+    let matches = MyTx::sgl().query(&pattern, None).collect::<Vec<_>>();
+    assert_eq!(matches.len(), 2);
+    let mut dxs = matches.iter().map(|m| m.get::<i64>("dx")).collect::<Vec<_>>();
+    dxs.sort();
+    assert_eq!(dxs, [1, 2]);
+    for m in &matches {
+        assert_eq!(m.get::<String>("shape"), "circle");
+        let p = m.term("p").unwrap().to_string();
+        assert!(p.starts_with("(OffsetPoint (CurAnchorOf \"circle\") "), "{p}");
+        // nodes of the term are created, their fields refer to each other by syms
+        assert!(m.get::<Point<MyTx>>("p").to_egglog().contains("(OffsetPoint "));
+        assert_eq!(m.vars().collect::<Vec<_>>(), ["p", "shape", "dx"]);
+        // dy is matched but not declared
+        assert!(m.term("dy").is_none());
+    }
+    assert_eq!(MyTx::sgl().query(&pattern, Some(1)).count(), 1);
+
+    // committed nodes are referred by their terms
+    let of_origin = Pattern::new("(OffsetPoint base dx dy)").eq("base", &origin);
+    assert_eq!(MyTx::sgl().query(&of_origin, None).count(), 1);
+}
+
+basic_tx_rx_vt!(MyTx);
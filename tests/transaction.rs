@@ -0,0 +1,64 @@
+use egglog_macros::egglog_ty;
+use egglog_wrapper::basic_tx_no_vt;
+use std::{
+    panic::{self, AssertUnwindSafe},
+    thread,
+};
+
+#[egglog_ty]
+enum Cons {
+    Value { v: i64, con: Box<Cons> },
+    End {},
+}
+
+#[test]
+fn nested_transaction_is_sent_by_outermost() {
+    let tx = MyTx::sgl();
+    let value = tx.transaction(|| {
+        let end = Cons::<MyTx>::new_end();
+        let value = tx.transaction(|| Cons::new_value(1, &end));
+        // inner transaction ended, but nothing is sent before the outermost one ends
+        assert!(tx.extract_term(value.cur_sym().as_str()).is_err());
+        value
+    });
+    assert_eq!(
+        tx.extract_term(value.cur_sym().as_str()),
+        Ok("(Value 1 (End))".to_owned())
+    );
+}
+
+#[test]
+fn transaction_is_sent_on_unwind() {
+    let tx = MyTx::sgl();
+    let mut sym = None;
+    let rst = panic::catch_unwind(AssertUnwindSafe(|| {
+        tx.transaction(|| {
+            sym = Some(Cons::<MyTx>::new_end().cur_sym());
+            panic!("scene building failed");
+        })
+    }));
+    assert!(rst.is_err());
+    assert_eq!(
+        tx.extract_term(sym.unwrap().as_str()),
+        Ok("(End)".to_owned())
+    );
+}
+
+#[test]
+fn transaction_is_scoped_to_thread() {
+    let tx = MyTx::sgl();
+    let (mine, theirs) = tx.transaction(|| {
+        let mine = Cons::<MyTx>::new_end().cur_sym();
+        let theirs = thread::spawn(|| Cons::<MyTx>::new_end().cur_sym())
+            .join()
+            .unwrap();
+        // code sent by other threads is interpreted at once
+        assert_eq!(tx.extract_term(theirs.as_str()), Ok("(End)".to_owned()));
+        assert!(tx.extract_term(mine.as_str()).is_err());
+        (mine, theirs)
+    });
+    assert_eq!(tx.extract_term(mine.as_str()), Ok("(End)".to_owned()));
+    assert_eq!(tx.extract_term(theirs.as_str()), Ok("(End)".to_owned()));
+}
+
+basic_tx_no_vt!(MyTx);
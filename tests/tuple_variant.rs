@@ -0,0 +1,56 @@
+use egglog_wrapper::{basic_tx_mock, wrap::*};
+
+// both modules define sort `Cons`, so sorts are only rendered here and never sent to an egraph
+mod tuple {
+    use egglog_macros::egglog_ty;
+
+    #[egglog_ty]
+    pub enum Cons {
+        Value(i64, Box<Cons>),
+        End,
+    }
+}
+
+mod named {
+    use egglog_macros::egglog_ty;
+
+    #[egglog_ty]
+    pub enum Cons {
+        Value { v: i64, con: Box<Cons> },
+        End {},
+    }
+}
+
+fn sort_defs<T: EgglogTy>() -> String {
+    let mut defs = SortDefs::default();
+    T::collect_sort_defs(&mut defs);
+    defs.defs().collect()
+}
+
+#[test]
+fn tuple_and_unit_variants_give_same_datatype() {
+    let def = sort_defs::<tuple::ConsTy>();
+    assert_eq!(def, "(Cons (Value i64 Cons) (End ))");
+    assert_eq!(def, sort_defs::<named::ConsTy>());
+}
+
+#[test]
+fn tuple_fields_are_set_by_position() {
+    let end = tuple::Cons::<MyTx>::new_end();
+    let mut value = tuple::Cons::new_value(1, &end);
+    assert_eq!(
+        value.to_egglog(),
+        format!("(let {} (Value 1 {}))", value.cur_sym(), end.cur_sym())
+    );
+    assert_eq!(value._1_sym().erase(), end.cur_sym());
+
+    let other = tuple::Cons::<MyTx>::new_end();
+    value.set_0(3).set_1(&other);
+    assert_eq!(
+        value.to_egglog(),
+        format!("(let {} (Value 3 {}))", value.cur_sym(), other.cur_sym())
+    );
+    assert_eq!(value.succs(), vec![other.cur_sym()]);
+}
+
+basic_tx_mock!(MyTx);
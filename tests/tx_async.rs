@@ -0,0 +1,89 @@
+use egglog_macros::egglog_ty;
+use egglog_wrapper::{basic_tx_async, query};
+
+#[egglog_ty]
+enum Cons {
+    Value { v: i64, con: Box<Cons> },
+    End {},
+}
+
+/// cheapest term of node's eclass, read on the worker after all commands sent before
+fn term<T: SingletonGetter<RetTy = egglog_wrapper::tx_async::TxAsync>>(
+    node: &impl EgglogNode,
+) -> Result<String, String> {
+    let sym = node.cur_sym().to_string();
+    T::sgl().with_egraph(move |egraph| query::extract(egraph, &sym))?
+}
+
+#[test]
+fn commands_keep_order_through_worker() {
+    let end = Cons::<OrderTx>::new_end();
+    end.commit();
+    let mut head = end.as_ref().clone();
+    // every commit refers to the node of the commit before, so it only succeeds in order
+    for v in 0..20 {
+        head = Cons::new_value(v, &head).as_ref().clone();
+        head.commit();
+    }
+    assert_eq!(OrderTx::sgl().flush(), Ok(()));
+    let term = term::<OrderTx>(&head).unwrap();
+    assert!(term.starts_with("(Value 19 (Value 18 "), "{term}");
+}
+
+#[test]
+fn flush_returns_failures_since_last_flush() {
+    let tx = FailTx::sgl();
+    tx.send(TxCommand::StringCommand {
+        string_command: "(unknown-command 1)".to_owned(),
+    });
+    let end = Cons::<FailTx>::new_end();
+    end.commit();
+    let failures = tx.flush().unwrap_err();
+    assert!(failures.contains("unknown-command"), "{failures}");
+    // failed program doesn't stop the worker, and is only reported once
+    assert_eq!(tx.flush(), Ok(()));
+    assert_eq!(term::<FailTx>(&end), Ok("(End)".to_owned()));
+}
+
+#[tokio::test]
+async fn flushed_resolves_under_tokio() {
+    let end = Cons::<AwaitTx>::new_end();
+    let value = Cons::new_value(1, &end);
+    value.commit();
+    let flushed = AwaitTx::sgl().flushed();
+    AwaitTx::sgl().send(TxCommand::StringCommand {
+        string_command: "(unknown-command 2)".to_owned(),
+    });
+    // future only waits for the commands sent before it is created
+    assert_eq!(flushed.await, Ok(()));
+    assert!(AwaitTx::sgl().flushed().await.is_err());
+}
+
+#[test]
+fn with_egraph_runs_after_sent_commands() {
+    let end = Cons::<EgraphTx>::new_end();
+    let value = Cons::new_value(7, &end);
+    value.commit();
+    // no flush, with_egraph is queued behind the commit
+    assert_eq!(term::<EgraphTx>(&value), Ok("(Value 7 (End))".to_owned()));
+}
+
+#[test]
+fn panic_in_with_egraph_keeps_worker_running() {
+    let tx = PanicTx::sgl();
+    let failure = tx
+        .with_egraph::<()>(|_| panic!("inspector failed"))
+        .unwrap_err();
+    assert!(failure.contains("inspector failed"), "{failure}");
+
+    let end = Cons::<PanicTx>::new_end();
+    end.commit();
+    assert_eq!(tx.flush(), Ok(()));
+    assert_eq!(term::<PanicTx>(&end), Ok("(End)".to_owned()));
+}
+
+basic_tx_async!(OrderTx);
+basic_tx_async!(FailTx);
+basic_tx_async!(AwaitTx);
+basic_tx_async!(EgraphTx);
+basic_tx_async!(PanicTx);
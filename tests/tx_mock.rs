@@ -0,0 +1,173 @@
+use egglog_macros::{egglog_func, egglog_ty};
+use egglog_wrapper::{basic_tx_mock, tx_mock::MockCall, wrap::FuncRows};
+
+#[egglog_ty]
+enum Cons {
+    Value { v: i64, con: Box<Cons> },
+    End {},
+}
+
+#[egglog_ty]
+struct VecCon {
+    v: Vec<Cons>,
+}
+
+#[egglog_ty]
+enum Root {
+    V { v: VecCon },
+}
+
+#[egglog_func(output=Root)]
+struct Selected {}
+
+#[egglog_func(output=Cons)]
+struct Tail {
+    list: Cons,
+}
+
+#[test]
+fn calls_are_logged_in_order() {
+    let end = Cons::<LogTx>::new_end();
+    let mut node = Cons::new_value(1, &end);
+    let node_created = node.to_egglog();
+    let vec = VecCon::new(vec![&node]);
+    let root = Root::new_v(&vec);
+    node.set_v(2).stage();
+    root.commit();
+    Selected::<LogTx>::set((), &root);
+
+    assert_eq!(
+        LogTx::sgl().take_calls(),
+        vec![
+            MockCall::New {
+                sym: end.cur_sym(),
+                egglog: end.to_egglog()
+            },
+            MockCall::New {
+                sym: node.cur_sym(),
+                egglog: node_created
+            },
+            MockCall::New {
+                sym: vec.cur_sym(),
+                egglog: vec.to_egglog()
+            },
+            MockCall::New {
+                sym: root.cur_sym(),
+                egglog: root.to_egglog()
+            },
+            MockCall::Set {
+                sym: node.cur_sym(),
+                egglog: node.to_egglog()
+            },
+            MockCall::Stage {
+                sym: node.cur_sym()
+            },
+            MockCall::Commit {
+                sym: root.cur_sym()
+            },
+            MockCall::FuncSet {
+                func: "Selected",
+                input: vec![],
+                output: root.cur_sym()
+            },
+        ]
+    );
+    assert!(LogTx::sgl().calls().is_empty());
+}
+
+#[test]
+fn scripted_func_get_is_read_as_typed_node() {
+    GetTx::sgl().script_func_get::<Selected<GetTx>>("(V (vec-of (Value 3 (End))))");
+    let selected = Selected::<GetTx>::get(());
+    let calls = GetTx::sgl().take_calls();
+    assert_eq!(
+        calls[0],
+        MockCall::FuncGet {
+            func: "Selected",
+            input: vec![]
+        }
+    );
+    // nodes of the answer are created bottom up by GetTx
+    let created = calls[1..]
+        .iter()
+        .map(|call| match call {
+            MockCall::New { egglog, .. } => egglog.clone(),
+            call => panic!("expect creation of node, found {call:?}"),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(created.len(), 4);
+    assert!(created[1].contains("(Value 3 "), "{}", created[1]);
+    assert_eq!(*created.last().unwrap(), selected.to_egglog());
+    let (ctor, fields) = selected.ctor_fields().unwrap();
+    assert_eq!(ctor, "V");
+    assert!(created[2].contains(&fields[0]), "{}", created[2]);
+}
+
+#[test]
+fn scripted_funcs_get_is_read_as_typed_rows() {
+    let rows = [1, 2, 3]
+        .map(|v| (format!("(Tail (Value {v} (End)))"), "(End)".to_owned()))
+        .to_vec();
+    RowsTx::sgl().script_funcs_get::<Tail<RowsTx>>(rows);
+
+    let page = Tail::<RowsTx>::get_page(1, Some(1));
+    assert_eq!(
+        RowsTx::sgl().calls()[0],
+        MockCall::FuncsGet {
+            func: "Tail",
+            offset: 1,
+            max_size: Some(1)
+        }
+    );
+    assert_eq!(page.len(), 1);
+    let ((list,), output) = &page[0];
+    assert!(
+        list.to_egglog().contains("(Value 2 "),
+        "{}",
+        list.to_egglog()
+    );
+    assert!(
+        output.to_egglog().contains("(End)"),
+        "{}",
+        output.to_egglog()
+    );
+
+    let all = Tail::<RowsTx>::get_all(None);
+    assert_eq!(all.len(), 3);
+    for (((list,), _), v) in all.iter().zip(1..) {
+        assert!(list.to_egglog().contains(&format!("(Value {v} ")));
+    }
+}
+
+#[test]
+fn func_rows_are_read_in_pages() {
+    let rows = [1, 2, 3, 4, 5]
+        .map(|v| (format!("(Tail (Value {v} (End)))"), "(End)".to_owned()))
+        .to_vec();
+    PagedTx::sgl().script_funcs_get::<Tail<PagedTx>>(rows);
+
+    let heads = FuncRows::<Tail<PagedTx>, PagedTx>::with_page_size(2)
+        .map(|((list,), _)| match list.node.ty {
+            ConsInner::Value { v, .. } => v,
+            ty => panic!("expect Value, found {ty:?}"),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(heads, [1, 2, 3, 4, 5]);
+    // the short page is the last one
+    let pages = PagedTx::sgl()
+        .take_calls()
+        .into_iter()
+        .filter_map(|call| match call {
+            MockCall::FuncsGet {
+                offset, max_size, ..
+            } => Some((offset, max_size)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(pages, [(0, Some(2)), (2, Some(2)), (4, Some(2))]);
+}
+
+basic_tx_mock!(LogTx);
+basic_tx_mock!(GetTx);
+basic_tx_mock!(RowsTx);
+basic_tx_mock!(PagedTx);
@@ -0,0 +1,43 @@
+use egglog_macros::{egglog_func, egglog_ty};
+use egglog_wrapper::{basic_tx_record, tx_record::TxRecord};
+use std::{env, fs, process};
+
+#[egglog_ty]
+enum Cons {
+    Value { v: i64, con: Box<Cons> },
+    End {},
+}
+
+#[egglog_func(output=Cons)]
+struct Head {}
+
+#[test]
+fn replayed_transcript_gives_same_egraph() {
+    let node1 = Cons::new_value(1, &Cons::<MyTx>::new_end());
+    let mut node2 = Cons::new_value(2, &node1);
+    node2.commit();
+    node2.set_v(4).stage();
+    node2.commit();
+    Head::<MyTx>::set((), &node2);
+
+    let path = env::temp_dir().join(format!("tx_record_{}.egg", process::id()));
+    MyTx::sgl().save(&path).unwrap();
+    let replayed = TxRecord::load(&path).unwrap();
+    let replayed_again = TxRecord::load(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    MyTx::sgl().assert_same_egraph(&replayed);
+    replayed.assert_same_egraph(&replayed_again);
+}
+
+#[test]
+#[should_panic(expected = "rows of Value differ")]
+fn changed_transcript_gives_other_egraph() {
+    let list = Cons::new_value(1, &Cons::<OtherTx>::new_end());
+    list.commit();
+    let changed = TxRecord::replay(&(OtherTx::sgl().transcript() + "(Value 9 (End))\n")).unwrap();
+    OtherTx::sgl().assert_same_egraph(&changed);
+}
+
+basic_tx_record!(MyTx);
+basic_tx_record!(OtherTx);
@@ -0,0 +1,170 @@
+use egglog_macros::{egglog_func, egglog_ty};
+use egglog_wrapper::{
+    basic_tx_remote,
+    tx_remote::{MAX_FRAME_LEN, TxRemote, serve},
+};
+use std::{
+    io::{self, Cursor, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::OnceLock,
+    thread,
+};
+
+#[egglog_ty]
+enum Cons {
+    Value { v: i64, con: Box<Cons> },
+    End {},
+}
+
+#[egglog_func(output=Cons)]
+struct Head {}
+
+#[egglog_func(output=Cons)]
+struct Tail {
+    list: Cons,
+}
+
+/// egraph server on a free port of localhost, shared by all tests
+fn server_addr() -> SocketAddr {
+    static ADDR: OnceLock<SocketAddr> = OnceLock::new();
+    *ADDR.get_or_init(|| {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind egraph server");
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || serve(listener.incoming()));
+        addr
+    })
+}
+
+#[test]
+fn command_and_extract() {
+    let end = Cons::<MyTx>::new_end();
+    let mut node = Cons::new_value(1, &end);
+    let mut list = Cons::new_value(2, &node);
+    list.commit();
+    assert_eq!(
+        MyTx::sgl().extract_term(list.cur_sym().as_str()).unwrap(),
+        "(Value 2 (Value 1 (End)))"
+    );
+    Head::<MyTx>::set((), &list);
+    assert_eq!(
+        MyTx::sgl().extract_term("(Head)").unwrap(),
+        "(Value 2 (Value 1 (End)))"
+    );
+    assert!(Head::<MyTx>::get(()).to_egglog().contains("(Value 2 "));
+
+    node.set_v(3).stage();
+    list.commit();
+    list.locate_latest();
+    assert_eq!(
+        MyTx::sgl().extract_term(list.cur_sym().as_str()).unwrap(),
+        "(Value 2 (Value 3 (End)))"
+    );
+    assert!(MyTx::sgl().extract_term("(NoSuchFunction)").is_err());
+}
+
+#[test]
+fn funcs_get() {
+    let lists = (0..3)
+        .map(|i| Cons::new_value(i, &Cons::<MyTx>::new_end()))
+        .collect::<Vec<_>>();
+    let end = Cons::<MyTx>::new_end();
+    end.commit();
+    for list in &lists {
+        list.commit();
+        Tail::<MyTx>::set((list,), &end);
+    }
+
+    let rows = MyTx::sgl().function_rows("Tail", 0, None).unwrap();
+    assert_eq!(rows.len(), 3);
+    assert!(
+        rows.iter()
+            .all(|(call, output)| call.starts_with("(Tail (Value ") && output == "(End)")
+    );
+    assert_eq!(MyTx::sgl().function_rows("Tail", 1, Some(5)).unwrap(), rows[1..]);
+    assert!(MyTx::sgl().function_rows("NoSuchFunction", 0, None).is_err());
+
+    let typed = Tail::<MyTx>::get_all(None);
+    assert_eq!(typed.len(), 3);
+    assert_eq!(Tail::<MyTx>::get_page(2, Some(2)).len(), 1);
+    assert_eq!(Tail::<MyTx>::iter_all().count(), 3);
+}
+
+/// stream answering every request by a scripted `ok` frame
+struct ScriptedServer {
+    responses: Vec<String>,
+    reading: Cursor<Vec<u8>>,
+}
+
+impl Read for ScriptedServer {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.reading.read(buf)
+    }
+}
+
+impl Write for ScriptedServer {
+    /// each flush ends a request frame, so the next response becomes readable
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        let payload = self.responses.remove(0);
+        self.reading = Cursor::new(format!("ok {}\n{}", payload.len(), payload).into_bytes());
+        Ok(())
+    }
+}
+
+#[test]
+fn odd_funcs_get_response_is_protocol_error() {
+    let server = ScriptedServer {
+        // response of hello, then a list of a single item
+        responses: vec![String::new(), "6\n(Tail)".to_owned()],
+        reading: Cursor::new(vec![]),
+    };
+    let tx = TxRemote::connect_with_type_defs(server, String::new()).unwrap();
+    let e = tx.function_rows("Tail", 0, None).unwrap_err();
+    assert!(e.contains("protocol error"), "{e}");
+}
+
+#[test]
+fn oversized_frame_closes_connection() {
+    let mut stream = TcpStream::connect(server_addr()).unwrap();
+    writeln!(stream, "hello {}", usize::MAX).unwrap();
+    // server neither allocates the payload nor answers, it closes the connection
+    let mut response = vec![];
+    stream.read_to_end(&mut response).unwrap();
+    assert!(response.is_empty());
+
+    // the server keeps serving other connections
+    assert!(MyTx::sgl().extract_term("(NoSuchFunction)").is_err());
+}
+
+/// stream answering the first request by raw bytes
+struct RawServer(Cursor<Vec<u8>>);
+
+impl Read for RawServer {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl Write for RawServer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn oversized_response_is_rejected_by_client() {
+    let server = RawServer(Cursor::new(
+        format!("ok {}\n", MAX_FRAME_LEN + 1).into_bytes(),
+    ));
+    let Err(e) = TxRemote::connect_with_type_defs(server, String::new()) else {
+        panic!("oversized hello response is accepted");
+    };
+    assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+}
+
+basic_tx_remote!(MyTx, server_addr());
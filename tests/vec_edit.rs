@@ -0,0 +1,82 @@
+use egglog_macros::egglog_ty;
+use egglog_wrapper::basic_tx_vt;
+
+#[egglog_ty]
+enum Ctl {
+    Wait { secs: f64 },
+    Seq { vec_ctl: VecCtl },
+}
+
+#[egglog_ty]
+struct VecCtl {
+    v: Vec<Ctl>,
+}
+
+fn waits<const N: usize>() -> [Ctl<MyTx>; N] {
+    std::array::from_fn(|i| Ctl::<MyTx>::new_wait(i as f64).as_ref().clone())
+}
+
+#[test]
+fn edits_change_elements() {
+    let [w0, w1, w2] = waits();
+    let mut timeline = VecCtl::<MyTx>::new(vec![&w0]);
+    timeline.push(&w1).insert(0, &w2);
+    assert_eq!(timeline.len(), 3);
+    assert_eq!(
+        timeline.succs(),
+        vec![w2.cur_sym(), w0.cur_sym(), w1.cur_sym()]
+    );
+    timeline.replace(1, &w1).remove(2);
+    assert_eq!(timeline.succs(), vec![w2.cur_sym(), w1.cur_sym()]);
+    timeline.set_all(vec![&w0, &w1]);
+    assert_eq!(
+        timeline.to_egglog(),
+        format!(
+            "(let {} (vec-of {} {}))",
+            timeline.cur_sym(),
+            w0.cur_sym(),
+            w1.cur_sym()
+        )
+    );
+}
+
+#[test]
+fn staged_edits_are_committed_as_new_versions() {
+    let [w0, w1, w2] = waits();
+    let mut timeline = VecCtl::<MyTx>::new(vec![&w0]);
+    let mut seq = Ctl::new_seq(&timeline);
+    seq.commit();
+    let first = timeline.cur_sym();
+
+    timeline.push(&w1).stage();
+    seq.commit();
+    timeline.locate_latest();
+    let second = timeline.cur_sym();
+    assert_ne!(second, first);
+    assert_eq!(timeline.succs(), vec![w0.cur_sym(), w1.cur_sym()]);
+    seq.locate_latest();
+    assert_eq!(seq.succs(), vec![second]);
+
+    timeline.replace(0, &w2).remove(1).stage();
+    seq.commit();
+    timeline.locate_latest();
+    assert_ne!(timeline.cur_sym(), second);
+    assert_eq!(timeline.succs(), vec![w2.cur_sym()]);
+    assert_eq!(MyTx::sgl().locate_prev(timeline.cur_sym()), second);
+}
+
+#[test]
+fn vec_node_commits_as_root() {
+    let [w0, w1] = waits();
+    let mut timeline = VecCtl::<MyTx>::new(vec![&w0]);
+    timeline.commit();
+    let first = timeline.cur_sym();
+
+    timeline.set_all(vec![&w1, &w0]).stage();
+    timeline.commit();
+    timeline.locate_latest();
+    assert_ne!(timeline.cur_sym(), first);
+    assert_eq!(timeline.succs(), vec![w1.cur_sym(), w0.cur_sym()]);
+}
+
+basic_tx_vt!(MyTx);